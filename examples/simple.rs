@@ -1,13 +1,11 @@
 //! This binary crate demonstrates the use of the RING API client.
 
 use std::env;
-use std::thread;
 use std::fmt::Debug;
 use std::time::Duration;
 use ring_api::{
     client::Client,
     requests::*,
-    job::*,
     settings::*,
 };
 
@@ -40,29 +38,12 @@ fn main() -> Result<(), Box<dyn Debug + 'static>> {
 
     println!("Submitted; response = {:#?}", submit_resp);
 
-    // Wait for the result to be ready; poll status endpoint
-    loop {
-        let req = Status {
-            job_id: submit_resp.job_id.clone()
-        };
-        let status_resp = client.send(req).map_err(|e| Box::new(e) as _)?;
-
-        println!("Job ID: {}, status: {}", status_resp.job_id, status_resp.status);
-
-        match status_resp.status {
-            JobStatus::Failed => return Err(Box::new(
-                format!("Job failed: {:#?}", status_resp)
-            )),
-            JobStatus::Complete => break,
-            _ => thread::sleep(Duration::from_secs(5)),
-        }
-    }
-
-    // Retrieve results
-    let req = RetrieveResult {
-        job_id: submit_resp.job_id.clone()
-    };
-    let result_resp = client.send(req).map_err(|e| Box::new(e) as _)?;
+    // Wait for the result to be ready, then retrieve it
+    let result_resp = client.wait_for_result(
+        &submit_resp.job_id,
+        Duration::from_secs(5),
+        None,
+    ).map_err(|e| Box::new(e) as _)?;
 
     println!("{:#?}", result_resp);
 
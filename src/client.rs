@@ -1,44 +1,844 @@
 //! A RING HTTP API client.
 
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::sync::{ Arc, Mutex, MutexGuard, RwLock, PoisonError };
+use std::thread::{ self, sleep };
+use std::panic::resume_unwind;
+use std::time::{ Duration, Instant };
 use serde::Serialize;
-use reqwest::{ Client as ReqwestClient, RequestBuilder };
+use http::Response as HttpResponse;
+use reqwest::{
+    Client as ReqwestClient, ClientBuilder as ReqwestClientBuilder,
+    RequestBuilder, Response, Method, StatusCode, Url, Proxy,
+    header::{
+        HeaderMap, HeaderValue, RETRY_AFTER, ETAG, LAST_MODIFIED, CONTENT_TYPE, USER_AGENT, AUTHORIZATION,
+        IF_NONE_MATCH, IF_MODIFIED_SINCE,
+    },
+};
 use crate::{
-    requests::{ Request, RequestBody },
-    error::Result,
+    requests::{
+        Request, RequestBody, SubmitResponse, ServerVersion, DownloadStructure,
+        Status, RetrieveResult, RetrieveResultResponse, Edge,
+        is_idempotent_method,
+    },
+    job::{ JobId, JobStatus },
+    error::{ Error, Result },
     multipart::to_form,
+    transport::Transport,
 };
+#[cfg(feature = "petgraph")]
+use crate::requests::Node;
 
-/// The base URL for the RING API.
+/// The base URL of the public RING API.
 static BASE_URL: &str = "http://protein.bio.unipd.it/ringws";
 
+/// The default `User-Agent` header, identifying this crate and its version.
+static USER_AGENT_STRING: &str = concat!("ring_api/", env!("CARGO_PKG_VERSION"));
+
 /// The main entry point to the RING webservice.
+///
+/// Cloning a client is cheap: clones share the same connection pool and
+/// configuration, so create one client (e.g. with `Client::builder()`) and
+/// clone it for each thread or task, instead of creating a new client for
+/// each. Clones also share the same user-supplied job tags, the same
+/// authentication token, and, if enabled, the same response cache.
+///
+/// Outgoing requests and the status of their responses are logged at the
+/// `debug` level, and response headers at the `trace` level, through the
+/// `log` crate, so install a logger to see them.
 #[derive(Debug, Clone)]
 pub struct Client {
-    /// The backing HTTP client.
+    /// The HTTP client used for building requests.
     client: ReqwestClient,
+    /// Sends the built requests; by default, the same HTTP client.
+    transport: Arc<dyn Transport>,
+    /// The URL which endpoints are appended to, without a trailing slash.
+    base_url: Arc<str>,
+    /// User-supplied labels of jobs, keyed by job ID.
+    tags: Arc<Mutex<HashMap<JobId, String>>>,
+    /// Cache of revalidatable `GET` responses, keyed by URL, if enabled.
+    cache: Option<Arc<Mutex<HashMap<String, CachedResponse>>>>,
+    /// When and how to retry failed requests.
+    retry: RetryPolicy,
+    /// The value of the `User-Agent` header sent with every request, if any.
+    /// `None` leaves it up to the backing `reqwest::Client`.
+    user_agent: Option<HeaderValue>,
+    /// The `Authorization` header sent with every request, if any.
+    auth: Arc<RwLock<Option<HeaderValue>>>,
+}
+
+/// Describes when and how failed requests are retried.
+///
+/// Requests are retried on connection errors, timeouts, 5xx (server
+/// error) and `429 Too Many Requests` responses. The delay before the
+/// `n`th retry (counting from 0) is `base_delay * 2^n`, or the delay
+/// requested by the server in a `Retry-After` header, whichever is longer,
+/// but never longer than `max_delay`. By default, only idempotent requests
+/// (e.g. `GET`) are retried, so that a retried submission can't create
+/// duplicate jobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetryPolicy {
+    /// The maximal number of retries after the first attempt.
+    pub max_retries: u32,
+    /// The delay before the first retry.
+    pub base_delay: Duration,
+    /// The longest delay before any retry, even if the server asks for a
+    /// longer one. Defaults to `RetryPolicy::DEFAULT_MAX_DELAY`.
+    pub max_delay: Duration,
+    /// Whether to retry non-idempotent requests, e.g. `POST` submissions.
+    pub retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// The default upper bound on the delay before a retry.
+    pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+    /// A policy which retries idempotent requests at most `max_retries`
+    /// times, with exponential backoff starting at `base_delay`.
+    pub fn new(max_retries: u32, base_delay: Duration) -> Self {
+        RetryPolicy {
+            max_retries,
+            base_delay,
+            max_delay: Self::DEFAULT_MAX_DELAY,
+            retry_non_idempotent: false,
+        }
+    }
+
+    /// A policy which never retries. This is the default.
+    pub fn never() -> Self {
+        Self::new(0, Duration::from_secs(0))
+    }
+
+    /// Builder method for opting in to retrying non-idempotent requests.
+    pub fn retry_non_idempotent(self, retry: bool) -> Self {
+        RetryPolicy { retry_non_idempotent: retry, ..self }
+    }
+
+    /// Builder method for capping the delay before each retry.
+    pub fn max_delay(self, max_delay: Duration) -> Self {
+        RetryPolicy { max_delay, ..self }
+    }
+
+    /// The delay before the retry with the given (0-based) index,
+    /// at most `max_delay`.
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        self.base_delay
+            .checked_mul(2_u32.saturating_pow(retry))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+
+    /// Whether requests with the given method may be retried.
+    /// Requests can further restrict this, see `Request::is_idempotent()`.
+    pub fn allows(&self, method: &Method) -> bool {
+        self.retry_non_idempotent || is_idempotent_method(method)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::never()
+    }
+}
+
+/// Metadata of a response: its status code and headers.
+/// Returned by `Client::send_with_meta()`.
+#[derive(Debug, Clone)]
+pub struct ResponseMeta {
+    /// The HTTP status code. This is `304 Not Modified` if the response
+    /// was served from the response cache after revalidation.
+    pub status: StatusCode,
+    /// The headers of the response.
+    pub headers: HeaderMap,
+}
+
+impl ResponseMeta {
+    /// Whether the server replied with `304 Not Modified`, i.e. the response
+    /// was served from the response cache, unchanged since it was cached.
+    pub fn is_not_modified(&self) -> bool {
+        self.status == StatusCode::NOT_MODIFIED
+    }
+
+    /// The delay requested by the server in the `Retry-After` header.
+    /// Only the delay-seconds form is supported, not HTTP dates.
+    pub fn retry_after(&self) -> Option<Duration> {
+        retry_after(&self.headers)
+    }
+
+    /// The number of requests remaining in the current rate limit window,
+    /// as reported by the `RateLimit-Remaining` or `X-RateLimit-Remaining`
+    /// header, if any.
+    pub fn rate_limit_remaining(&self) -> Option<u64> {
+        ["ratelimit-remaining", "x-ratelimit-remaining"]
+            .iter()
+            .filter_map(|name| self.headers.get(*name))
+            .filter_map(|value| value.to_str().ok()?.trim().parse().ok())
+            .next()
+    }
+}
+
+/// A response body along with the validators the server sent with it.
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    /// The value of the `ETag` header.
+    etag: Option<HeaderValue>,
+    /// The value of the `Last-Modified` header.
+    last_modified: Option<HeaderValue>,
+    /// The value of the `Content-Type` header, needed for decoding text.
+    content_type: Option<HeaderValue>,
+    /// The raw, undecoded body.
+    body: Vec<u8>,
+}
+
+/// Builder for configuring a `Client`.
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    /// The base URL, see `Client::with_base_url()`.
+    base_url: String,
+    /// The timeout of each HTTP request, if any.
+    timeout: Option<Duration>,
+    /// Whether to cache responses, see `Client::with_response_cache()`.
+    response_cache: bool,
+    /// When and how to retry failed requests.
+    retry: RetryPolicy,
+    /// The `User-Agent` header, see `Client::with_user_agent()`.
+    user_agent: String,
+    /// Whether to request and transparently decompress gzipped responses.
+    gzip: bool,
+    /// The transport, see `Client::with_transport()`, if not the default.
+    transport: Option<Arc<dyn Transport>>,
+    /// The proxies to route requests through, see `Client::with_proxy()`.
+    proxies: Vec<Proxy>,
+}
+
+impl ClientBuilder {
+    /// Creates a builder with the default configuration: the public RING
+    /// service, a 30-second timeout (the default of `reqwest`), no
+    /// response cache, a `User-Agent` of `ring_api/<version>`, and
+    /// gzip compression enabled.
+    pub fn new() -> Self {
+        ClientBuilder {
+            base_url: String::from(BASE_URL),
+            timeout: Some(Duration::from_secs(30)),
+            response_cache: false,
+            retry: RetryPolicy::default(),
+            user_agent: String::from(USER_AGENT_STRING),
+            gzip: true,
+            transport: None,
+            proxies: Vec::new(),
+        }
+    }
+
+    /// Sets the base URL that endpoints are appended to. It is validated
+    /// by `build()`; see `Client::with_base_url()` for the requirements.
+    pub fn base_url<T: Into<String>>(self, url: T) -> Self {
+        ClientBuilder { base_url: url.into(), ..self }
+    }
+
+    /// Sets the timeout for connecting to, reading from and writing to the
+    /// server, for each request made by `send()`. `None` means no timeout.
+    /// When the timeout elapses, `send()` returns an `Error::Reqwest`.
+    pub fn timeout<T: Into<Option<Duration>>>(self, timeout: T) -> Self {
+        ClientBuilder { timeout: timeout.into(), ..self }
+    }
+
+    /// Enables or disables the in-memory cache of revalidatable responses.
+    pub fn response_cache(self, enabled: bool) -> Self {
+        ClientBuilder { response_cache: enabled, ..self }
+    }
+
+    /// Sets the policy for retrying failed requests. By default, requests
+    /// are not retried.
+    pub fn retry(self, retry: RetryPolicy) -> Self {
+        ClientBuilder { retry, ..self }
+    }
+
+    /// Sets the `User-Agent` header sent with every request.
+    pub fn user_agent<T: Into<String>>(self, user_agent: T) -> Self {
+        ClientBuilder { user_agent: user_agent.into(), ..self }
+    }
+
+    /// Enables or disables gzip compression of responses. When enabled,
+    /// requests are sent with `Accept-Encoding: gzip`, and compressed
+    /// responses (e.g. the large JSON of results) are decompressed before
+    /// decoding. Support for this is always built into `reqwest`, so
+    /// there is no need for a cargo feature (the `gzip-files` feature is
+    /// only for reading compressed files); it is enabled by default,
+    /// including for `Client::new()`.
+    pub fn gzip(self, enabled: bool) -> Self {
+        ClientBuilder { gzip: enabled, ..self }
+    }
+
+    /// Routes requests through the given proxy. May be called several times,
+    /// e.g. with separate proxies for `http` and `https`; they are tried in
+    /// the order they were added. Credentials can be set on the proxy with
+    /// `Proxy::basic_auth()`.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Sends requests through the given transport instead of `reqwest`.
+    /// See `Client::with_transport()`.
+    pub fn transport<T: Transport + 'static>(self, transport: T) -> Self {
+        ClientBuilder { transport: Some(Arc::new(transport)), ..self }
+    }
+
+    /// Creates a client with this configuration. Fails if the underlying
+    /// HTTP client can't be initialized, or if the user agent is not a
+    /// valid header value.
+    pub fn build(self) -> Result<Client> {
+        let client = self.proxies
+            .into_iter()
+            .fold(ReqwestClientBuilder::new(), ReqwestClientBuilder::proxy)
+            .timeout(self.timeout)
+            .gzip(self.gzip)
+            .build()?;
+        let user_agent = user_agent_header(&self.user_agent)?;
+
+        validate_base_url(&self.base_url)?;
+
+        let default = Client::from_reqwest(client);
+
+        Ok(Client {
+            base_url: self.base_url.trim_end_matches('/').into(),
+            cache: if self.response_cache { Some(Arc::default()) } else { None },
+            retry: self.retry,
+            user_agent: Some(user_agent),
+            transport: self.transport.unwrap_or_else(|| default.transport.clone()),
+            ..default
+        })
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Client {
-    /// Creates a RING web client.
+    /// Creates a RING web client for the public RING service.
     pub fn new() -> Self {
         Client {
-            client: ReqwestClient::new()
+            user_agent: Some(HeaderValue::from_static(USER_AGENT_STRING)),
+            ..Self::from_reqwest(ReqwestClient::new())
+        }
+    }
+
+    /// Creates a RING web client for the public RING service, which sends
+    /// requests using the given, fully user-configured HTTP client. This
+    /// allows setting proxies, custom root certificates, connection pool
+    /// limits, etc. The timeout of `client` is used as-is, and so is its
+    /// `User-Agent`: if `client` has none among its default headers,
+    /// requests are sent without one, not with `ring_api/<version>`.
+    pub fn from_reqwest(client: ReqwestClient) -> Self {
+        Client {
+            transport: Arc::new(client.clone()),
+            client,
+            base_url: Arc::from(BASE_URL),
+            tags: Arc::default(),
+            cache: None,
+            retry: RetryPolicy::default(),
+            user_agent: None,
+            auth: Arc::default(),
+        }
+    }
+
+    /// Creates a RING web client which sends requests through the given
+    /// transport, e.g. a `MockTransport` in tests, instead of `reqwest`.
+    ///
+    /// Requests are still built using `reqwest` types. The timeout and
+    /// gzip settings only apply to the default transport; a custom one is
+    /// responsible for its own.
+    pub fn with_transport<T: Transport + 'static>(transport: T) -> Self {
+        Client {
+            transport: Arc::new(transport),
+            ..Client::new()
+        }
+    }
+
+    /// Creates a RING web client for the public RING service, which
+    /// identifies itself with the given `User-Agent` header instead of
+    /// the default `ring_api/<version>`. Fails if `user_agent` is not
+    /// a valid header value, e.g. because it contains a newline.
+    pub fn with_user_agent<T: AsRef<str>>(user_agent: T) -> Result<Self> {
+        Ok(Client {
+            user_agent: Some(user_agent_header(user_agent.as_ref())?),
+            ..Client::new()
+        })
+    }
+
+    /// Returns a builder for configuring a client.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Creates a RING web client for the public RING service, with the
+    /// given timeout for each request. By default (i.e. with `new()`), the
+    /// timeout is 30 seconds; pass `None` to wait indefinitely.
+    pub fn with_timeout<T: Into<Option<Duration>>>(timeout: T) -> Result<Self> {
+        Self::builder().timeout(timeout).build()
+    }
+
+    /// Creates a RING web client for the public RING service, which sends
+    /// requests through the given (e.g. authenticated) proxy. To combine the
+    /// proxy with other settings, use `ClientBuilder::proxy()`.
+    pub fn with_proxy(proxy: Proxy) -> Result<Self> {
+        Self::builder().proxy(proxy).build()
+    }
+
+    /// Creates a RING web client which sends requests to the given base URL,
+    /// e.g. a private mirror of the RING service. The endpoint of each
+    /// request will be appended to it.
+    ///
+    /// Both `http` and `https` URLs are supported; TLS is provided by the
+    /// default features of `reqwest` (`default-tls`, i.e. `native-tls`).
+    /// Returns an `Error::Validation` if the URL is malformed or has any
+    /// other scheme.
+    pub fn with_base_url<T: Into<String>>(url: T) -> Result<Self> {
+        Self::builder().base_url(url).build()
+    }
+
+    /// Creates a RING web client for the public RING service, which retries
+    /// idempotent requests (e.g. status queries and result retrieval) at most
+    /// `max_retries` times, with exponential backoff. See `RetryPolicy`.
+    pub fn with_retry(max_retries: u32, base_delay: Duration) -> Self {
+        Client {
+            retry: RetryPolicy::new(max_retries, base_delay),
+            ..Client::new()
+        }
+    }
+
+    /// Creates a RING web client for the public RING service, which
+    /// authenticates every request with the given token, sent as
+    /// `Authorization: Bearer <token>`. Fails if the token is not
+    /// a valid header value.
+    pub fn with_auth_token<T: AsRef<str>>(token: T) -> Result<Self> {
+        let client = Client::new();
+        client.set_auth_token(token)?;
+        Ok(client)
+    }
+
+    /// Replaces the authentication token, e.g. when it has expired.
+    /// Since the token is shared, this affects all clones of this client.
+    /// On error, the previous token is kept.
+    pub fn set_auth_token<T: AsRef<str>>(&self, token: T) -> Result<()> {
+        let header = format!("Bearer {}", token.as_ref());
+        let mut value = HeaderValue::from_str(&header).map_err(
+            |_| Error::Validation(String::from("auth token is not a valid header value"))
+        )?;
+
+        value.set_sensitive(true);
+        *self.auth.write().unwrap_or_else(PoisonError::into_inner) = Some(value);
+
+        Ok(())
+    }
+
+    /// Stops sending an authentication token, for all clones of this client.
+    pub fn clear_auth_token(&self) {
+        *self.auth.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// Returns the base URL requests are sent to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Creates a RING web client which caches responses to `GET` requests
+    /// in memory, as long as the server sends an `ETag` or `Last-Modified`
+    /// header along with them. Subsequent requests for the same URL are
+    /// sent with `If-None-Match`/`If-Modified-Since`, and the cached body is
+    /// reused if the server replies with `304 Not Modified`.
+    ///
+    /// If the server ignores conditional headers or doesn't send validators,
+    /// this gracefully degrades to always downloading the full response.
+    ///
+    /// To find out whether the result of a request was reused, e.g. in order
+    /// to skip re-processing an unchanged `RetrieveResultResponse`, send it
+    /// with `send_with_meta()` and check `ResponseMeta::is_not_modified()`.
+    pub fn with_response_cache() -> Self {
+        Client {
+            cache: Some(Arc::default()),
+            ..Client::new()
+        }
+    }
+
+    /// Removes all entries from the response cache, if any.
+    pub fn clear_response_cache(&self) {
+        if let Some(ref cache) = self.cache {
+            cache.lock().unwrap_or_else(PoisonError::into_inner).clear();
         }
     }
 
     /// Sending requests.
     pub fn send<R: Request>(&self, request: R) -> Result<R::Response> {
+        self.send_with_meta(request).map(|(response, _)| response)
+    }
+
+    /// Like `send()`, but also returns the status code and the headers of
+    /// the response, e.g. for respecting rate limits reported by the server.
+    pub fn send_with_meta<R: Request>(&self, request: R) -> Result<(R::Response, ResponseMeta)> {
+        let endpoint = request.endpoint();
+        let url = format!("{}/{}", self.base_url, endpoint.trim_matches('/'));
+        let cache = self.cache.as_ref().filter(|_| R::METHOD == Method::GET && R::is_idempotent());
+        let cached = cache.and_then(|c| {
+            c.lock().unwrap_or_else(PoisonError::into_inner).get(&url).cloned()
+        });
+
+        let mut response = self.execute(&request, &url, cached.as_ref())?;
+        let meta = ResponseMeta {
+            status: response.status(),
+            headers: response.headers().clone(),
+        };
+
+        let decoded = match (cache, cached) {
+            (Some(_), Some(entry)) if response.status() == StatusCode::NOT_MODIFIED => {
+                R::decode(entry.to_response())
+            }
+            _ if !response.status().is_success() => {
+                Err(Error::Http {
+                    status: response.status(),
+                    body: response.text().unwrap_or_default(),
+                })
+            }
+            (Some(c), _) => {
+                let entry = CachedResponse::from_response(&mut response)?;
+                let replayed = entry.to_response();
+
+                if entry.etag.is_some() || entry.last_modified.is_some() {
+                    c.lock().unwrap_or_else(PoisonError::into_inner).insert(url, entry);
+                }
+
+                R::decode(replayed)
+            }
+            _ => R::decode(response)
+        };
+
+        decoded.map(|value| (value, meta))
+    }
+
+    /// Sends the request without consulting the response cache, and
+    /// returns the raw response if its status indicates success.
+    fn send_uncached<R: Request>(&self, request: &R) -> Result<Response> {
         let endpoint = request.endpoint();
-        let endpoint = endpoint.trim_matches('/');
-        let url = format!("{}/{}", BASE_URL, endpoint);
+        let url = format!("{}/{}", self.base_url, endpoint.trim_matches('/'));
+        let mut response = self.execute(request, &url, None)?;
+
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(Error::Http {
+                status: response.status(),
+                body: response.text().unwrap_or_default(),
+            })
+        }
+    }
+
+    /// Sends the request, retrying on transient failures if the retry
+    /// policy and the HTTP method of the request allow for it.
+    fn execute<R: Request>(
+        &self,
+        request: &R,
+        url: &str,
+        cached: Option<&CachedResponse>,
+    ) -> Result<Response> {
+        let retryable = self.retry.retry_non_idempotent || R::is_idempotent();
+        let mut attempt = 0;
+
+        loop {
+            let mut builder = self.client.request(R::METHOD, url);
+
+            if let Some(ref user_agent) = self.user_agent {
+                builder = builder.header(USER_AGENT, user_agent.clone());
+            }
+
+            if let Some(ref auth) = *self.auth.read().unwrap_or_else(PoisonError::into_inner) {
+                builder = builder.header(AUTHORIZATION, auth.clone());
+            }
+
+            builder = builder.headers(request.headers());
+
+            if let Some(entry) = cached {
+                if let Some(ref etag) = entry.etag {
+                    builder = builder.header(IF_NONE_MATCH, etag.clone());
+                }
+                if let Some(ref last_modified) = entry.last_modified {
+                    builder = builder.header(IF_MODIFIED_SINCE, last_modified.clone());
+                }
+            }
+
+            let body = request.body();
+
+            debug!("sending {} {} (body: {}, attempt {})", R::METHOD, url, body.kind(), attempt + 1);
+
+            let outcome = builder
+                .ring_body(body)?
+                .build()
+                .map_err(Error::from)
+                .and_then(|http_request| self.transport.execute(http_request));
+
+            match outcome {
+                Ok(ref response) => {
+                    debug!("{} {} responded with {}", R::METHOD, url, response.status());
+                    trace!("response headers: {:?}", response.headers());
+                }
+                Err(ref error) => debug!("{} {} failed: {}", R::METHOD, url, error),
+            }
+
+            let transient = match outcome {
+                Ok(ref response) => {
+                    response.status().is_server_error()
+                        || response.status() == StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(Error::Reqwest(ref error)) => is_transient(error),
+                Err(_) => false,
+            };
+
+            if transient && retryable && attempt < self.retry.max_retries {
+                let backoff = self.retry.delay_for(attempt);
+                let requested = outcome
+                    .as_ref()
+                    .ok()
+                    .and_then(|response| retry_after(response.headers()));
+
+                let delay = requested
+                    .map_or(backoff, |delay| delay.max(backoff))
+                    .min(self.retry.max_delay);
+
+                debug!("retrying {} {} in {:?}", R::METHOD, url, delay);
+                sleep(delay);
+                attempt += 1;
+            } else {
+                return outcome;
+            }
+        }
+    }
+
+    /// Polls the status of a job every `poll_interval` until it completes,
+    /// then retrieves its result. Keeps waiting while the job is in progress
+    /// or only partially complete.
+    ///
+    /// Returns `Error::JobFailed`, including the server's reason if any, if
+    /// the job fails, and `Error::JobTimeout` if it doesn't finish within
+    /// `timeout` (if any). The status is polled one last time when the
+    /// timeout expires, even if that is sooner than `poll_interval`.
+    pub fn wait_for_result(
+        &self,
+        job_id: &JobId,
+        poll_interval: Duration,
+        timeout: Option<Duration>,
+    ) -> Result<RetrieveResultResponse> {
+        self.wait_until(job_id, poll_interval, timeout, JobStatus::is_complete)
+    }
+
+    /// Like `wait_for_result()`, but retrieves the result as soon as the job
+    /// is at least partially complete. For jobs with `perform_msa` set, the
+    /// network is then already available, but MSA-derived fields (`entropy`,
+    /// `mutual_inf`, etc.) are `None`; the `status` of the result tells
+    /// whether it is partial. This allows processing the network while the
+    /// MSA is still being computed.
+    pub fn wait_for_partial_result(
+        &self,
+        job_id: &JobId,
+        poll_interval: Duration,
+        timeout: Option<Duration>,
+    ) -> Result<RetrieveResultResponse> {
+        self.wait_until(job_id, poll_interval, timeout, |status| {
+            status.is_complete() || *status == JobStatus::Partial
+        })
+    }
+
+    /// Private helper for polling a job until `ready` returns `true`
+    /// for its status, then retrieving its result.
+    fn wait_until<F: Fn(&JobStatus) -> bool>(
+        &self,
+        job_id: &JobId,
+        poll_interval: Duration,
+        timeout: Option<Duration>,
+        ready: F,
+    ) -> Result<RetrieveResultResponse> {
+        let start = Instant::now();
+
+        loop {
+            let status = self.send(Status { job_id: job_id.clone() })?;
+
+            if status.status.is_failed() {
+                return Err(Error::JobFailed {
+                    job_id: job_id.clone(),
+                    reason: status.failure_reason().map(Into::into),
+                });
+            }
+
+            if ready(&status.status) {
+                return self.send(RetrieveResult::new(job_id.clone()));
+            }
+
+            // never sleep past the deadline, but poll once more when it expires
+            let elapsed = start.elapsed();
+            let remaining = timeout.map(|limit| limit.checked_sub(elapsed).unwrap_or_default());
+
+            if remaining == Some(Duration::from_secs(0)) {
+                return Err(Error::JobTimeout { job_id: job_id.clone() });
+            }
+
+            sleep(remaining.map_or(poll_interval, |r| r.min(poll_interval)));
+        }
+    }
+
+    /// Submits a job, waits for it to complete, polling its status every
+    /// `poll_interval`, then retrieves its result. There is no timeout;
+    /// use `send()` and `wait_for_result()` separately if you need one,
+    /// or if you need the job ID in case of errors.
+    pub fn submit_and_wait<R>(
+        &self,
+        request: R,
+        poll_interval: Duration,
+    ) -> Result<RetrieveResultResponse>
+        where R: Request<Response = SubmitResponse>
+    {
+        let response = self.send(request)?;
+        self.wait_for_result(&response.job_id, poll_interval, None)
+    }
+
+    /// Submits many jobs, sending at most `max_concurrency` (but at least 1)
+    /// requests at a time, each on its own thread. Each submission is subject
+    /// to the retry policy, just like with `send()`.
+    ///
+    /// Returns the outcome of each submission in the order of `requests`.
+    /// A failed submission doesn't stop the others from being sent.
+    pub fn submit_many<R, I>(&self, requests: I, max_concurrency: usize) -> Vec<Result<SubmitResponse>>
+        where R: Request<Response = SubmitResponse> + Send,
+              I: IntoIterator<Item = R>,
+    {
+        let pending: Vec<_> = requests.into_iter().collect();
+        let count = pending.len();
+        let queue = Mutex::new(pending.into_iter().enumerate());
+        let mut outcomes: Vec<Option<Result<SubmitResponse>>> = (0..count).map(|_| None).collect();
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..max_concurrency.clamp(1, count.max(1)))
+                .map(|_| scope.spawn(|| {
+                    let mut sent = Vec::new();
+
+                    loop {
+                        let next = queue.lock().unwrap_or_else(PoisonError::into_inner).next();
+
+                        match next {
+                            Some((index, request)) => sent.push((index, self.send(request))),
+                            None => break sent,
+                        }
+                    }
+                }))
+                .collect();
+
+            for worker in workers {
+                for (index, outcome) in worker.join().unwrap_or_else(|panic| resume_unwind(panic)) {
+                    outcomes[index] = Some(outcome);
+                }
+            }
+        });
+
+        outcomes
+            .into_iter()
+            .map(|outcome| outcome.expect("every request is sent exactly once"))
+            .collect()
+    }
+
+    /// Asks the server which version of RING it is running.
+    /// Returns `None` if the server doesn't report its version,
+    /// including when it doesn't have a version endpoint at all.
+    pub fn server_version(&self) -> Result<Option<String>> {
+        match self.send(ServerVersion) {
+            Ok(response) => Ok(response.version),
+            Err(Error::Http { status: StatusCode::NOT_FOUND, .. }) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Checks whether the server knows about a job, by requesting its
+    /// status, which is much cheaper than retrieving its result.
+    /// Returns `false` if the server responds with 404 Not Found.
+    pub fn job_exists(&self, job_id: &JobId) -> Result<bool> {
+        match self.send_uncached(&Status { job_id: job_id.clone() }) {
+            Ok(_) => Ok(true),
+            Err(Error::Http { status: StatusCode::NOT_FOUND, .. }) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Retrieves the result of a job and converts it into a `petgraph` graph.
+    /// Fails just like sending a `RetrieveResult` request, and also with
+    /// `Error::Validation` if an edge refers to a node missing from the
+    /// result. See `RetrieveResultResponse::try_to_petgraph()`.
+    #[cfg(feature = "petgraph")]
+    pub fn retrieve_graph(&self, job_id: &JobId) -> Result<petgraph::Graph<Node, Edge>> {
+        self.send(RetrieveResult::new(job_id.clone()))?.try_to_petgraph()
+    }
+
+    /// Downloads the structure file (e.g. PDB) that a job was computed from.
+    pub fn download_structure(&self, job_id: &JobId) -> Result<String> {
+        self.send(DownloadStructure { job_id: job_id.clone() })
+    }
+
+    /// Retrieves the result of a job, passing each edge to `on_edge` while
+    /// the response is being downloaded, instead of collecting them.
+    /// The `edges` of the returned result are empty. See
+    /// `RetrieveResultResponse::from_reader_streaming()` for the tradeoffs
+    /// compared to sending a `RetrieveResult` request. The response cache
+    /// is bypassed, since caching would defeat the purpose of streaming.
+    pub fn retrieve_result_streaming<F: FnMut(Edge)>(
+        &self,
+        job_id: &JobId,
+        on_edge: F,
+    ) -> Result<RetrieveResultResponse> {
+        let response = self.send_uncached(&RetrieveResult::new(job_id.clone()))?;
+        RetrieveResultResponse::from_reader_streaming(BufReader::new(response), on_edge)
+    }
+
+    /// Submits a job and associates the given tag with the returned job ID.
+    ///
+    /// RING itself doesn't store arbitrary labels, so this is purely
+    /// client-side bookkeeping. Tags only live as long as this client
+    /// (and its clones); they are **not** persisted across process restarts.
+    pub fn submit_tagged<R, T>(&self, request: R, tag: T) -> Result<SubmitResponse>
+        where R: Request<Response = SubmitResponse>,
+              T: Into<String>,
+    {
+        let response = self.send(request)?;
+        self.set_tag(response.job_id.clone(), tag);
+        Ok(response)
+    }
+
+    /// Associates a tag with an existing job, replacing the previous one.
+    /// Returns the previous tag, if any.
+    pub fn set_tag<T: Into<String>>(&self, job_id: JobId, tag: T) -> Option<String> {
+        self.lock_tags().insert(job_id, tag.into())
+    }
 
-        self.client
-            .request(R::METHOD, &url)
-            .headers(request.headers())
-            .ring_body(request.body())?
-            .send()
-            .and_then(|mut resp| resp.json())
-            .map_err(From::from)
+    /// Removes the tag of a job, returning it if it was present.
+    pub fn remove_tag(&self, job_id: &JobId) -> Option<String> {
+        self.lock_tags().remove(job_id)
+    }
+
+    /// Returns the tag associated with the given job ID, if any.
+    pub fn tag_of(&self, job_id: &JobId) -> Option<String> {
+        self.lock_tags().get(job_id).cloned()
+    }
+
+    /// Returns the IDs of all jobs that were tagged with the given tag.
+    pub fn jobs_tagged<T: AsRef<str>>(&self, tag: T) -> Vec<JobId> {
+        self.lock_tags()
+            .iter()
+            .filter(|&(_, t)| t == tag.as_ref())
+            .map(|(job_id, _)| job_id.clone())
+            .collect()
+    }
+
+    /// Locks the tag registry. A poisoned lock is still usable, since
+    /// the map of tags can't be left in an inconsistent state.
+    fn lock_tags(&self) -> MutexGuard<'_, HashMap<JobId, String>> {
+        self.tags.lock().unwrap_or_else(PoisonError::into_inner)
     }
 }
 
@@ -48,6 +848,66 @@ impl Default for Client {
     }
 }
 
+impl CachedResponse {
+    /// Reads the body and the cache validators out of a response.
+    fn from_response(response: &mut Response) -> Result<Self> {
+        let etag = response.headers().get(ETAG).cloned();
+        let last_modified = response.headers().get(LAST_MODIFIED).cloned();
+        let content_type = response.headers().get(CONTENT_TYPE).cloned();
+        let mut body = Vec::new();
+
+        response.copy_to(&mut body)?;
+
+        Ok(CachedResponse { etag, last_modified, content_type, body })
+    }
+
+    /// Replays the cached body as a fresh, successful response,
+    /// so that it can be decoded by `Request::decode()`.
+    fn to_response(&self) -> Response {
+        let mut response = HttpResponse::new(self.body.clone());
+
+        if let Some(ref content_type) = self.content_type {
+            response.headers_mut().insert(CONTENT_TYPE, content_type.clone());
+        }
+
+        Response::from(response)
+    }
+}
+
+/// Parses the `Retry-After` header, if present, as a number of seconds.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// Whether a `reqwest` error is a connection failure or a timeout, which
+/// may go away if the request is retried. Errors in building the request
+/// (e.g. an invalid URL or header) are permanent, so they are excluded.
+fn is_transient(error: &reqwest::Error) -> bool {
+    let invalid_request = matches!(error.get_ref(), Some(cause) if cause.is::<http::Error>());
+    error.is_timeout() || (error.is_http() && !invalid_request)
+}
+
+/// Checks that a base URL is well-formed and has a supported scheme.
+fn validate_base_url(url: &str) -> Result<()> {
+    match Url::parse(url) {
+        Ok(ref parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => Ok(()),
+        Ok(parsed) => Err(Error::Validation(format!(
+            "unsupported scheme `{}` in base URL; use `http` or `https`", parsed.scheme()
+        ))),
+        Err(cause) => Err(Error::Validation(format!(
+            "invalid base URL `{}`: {}", url, cause
+        ))),
+    }
+}
+
+/// Converts a user agent string into a header value.
+fn user_agent_header(user_agent: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(user_agent).map_err(|_| Error::Validation(format!(
+        "`{}` is not a valid User-Agent header", user_agent.escape_debug()
+    )))
+}
+
 /// Private trait for extending the client builder so that it can send the body
 /// in different formats, decided dynamically.
 trait RequestBuilderExt: Sized {
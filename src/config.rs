@@ -0,0 +1,218 @@
+//! Human-friendly configuration files for job settings.
+//!
+//! The serde representation of `Settings` mirrors what the RING server
+//! expects on the wire, e.g. `seqSeparation` and stringly-typed booleans.
+//! `SettingsConfig` is the same set of parameters with natural snake_case
+//! names and types, meant for YAML, TOML, etc. configuration files.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use serde::{
+    ser::{ Serialize, Serializer },
+    de::{ Deserialize, Deserializer, Error as DeError },
+};
+#[cfg(any(feature = "yaml", feature = "toml"))]
+use crate::error::{ Error as RingError, Result as RingResult };
+use crate::settings::{ Settings, Chain, NetworkPolicy, InteractionType, Thresholds };
+
+/// The parameters of `Settings`, in a shape suitable for configuration files.
+///
+/// Every field is optional, and missing fields take the default values of
+/// `Settings`. Unknown fields are rejected, so that typos don't silently
+/// fall back to the defaults. Thresholds are given either as the name of
+/// a preset (`strict` or `relaxed`), or as a table of distances, e.g.
+///
+/// ```yaml
+/// chain: A
+/// network_policy: cb
+/// interactions: most_energetic
+/// thresholds:
+///   hydrogen: 4.0
+///   pi_pi: 7.0
+/// sequence_separation: 5
+/// ```
+///
+/// Thresholds missing from the table take their `strict` values.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SettingsConfig {
+    /// See `Settings::chain`.
+    pub chain: Chain,
+    /// See `Settings::network_policy`.
+    pub network_policy: NetworkPolicy,
+    /// See `Settings::interactions`.
+    #[serde(serialize_with = "serialize_display", deserialize_with = "deserialize_from_str")]
+    pub interactions: InteractionType,
+    /// See `Settings::thresholds`.
+    #[serde(serialize_with = "serialize_thresholds", deserialize_with = "deserialize_thresholds")]
+    pub thresholds: Thresholds,
+    /// See `Settings::sequence_separation`.
+    pub sequence_separation: usize,
+    /// See `Settings::skip_hetero`.
+    pub skip_hetero: bool,
+    /// See `Settings::skip_water`.
+    pub skip_water: bool,
+    /// See `Settings::skip_energy`.
+    pub skip_energy: bool,
+    /// See `Settings::perform_msa`.
+    pub perform_msa: bool,
+    /// See `Settings::ring_md`.
+    pub ring_md: bool,
+    /// See `Settings::model`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<usize>,
+}
+
+impl SettingsConfig {
+    /// Parses a configuration from YAML.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(yaml: &str) -> RingResult<Self> {
+        serde_yaml::from_str(yaml).map_err(|error| RingError::Parsing(error.into()))
+    }
+
+    /// Formats the configuration as YAML.
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml_string(&self) -> RingResult<String> {
+        serde_yaml::to_string(self).map_err(|error| RingError::Serialization(error.to_string()))
+    }
+
+    /// Parses a configuration from TOML.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> RingResult<Self> {
+        toml::from_str(toml).map_err(|error| RingError::Parsing(error.into()))
+    }
+
+    /// Formats the configuration as TOML.
+    #[cfg(feature = "toml")]
+    pub fn to_toml_string(&self) -> RingResult<String> {
+        toml::to_string(self).map_err(|error| RingError::Serialization(error.to_string()))
+    }
+}
+
+impl Default for SettingsConfig {
+    fn default() -> Self {
+        Settings::default().into()
+    }
+}
+
+impl From<SettingsConfig> for Settings {
+    fn from(config: SettingsConfig) -> Self {
+        Settings {
+            chain: config.chain,
+            network_policy: config.network_policy,
+            interactions: config.interactions,
+            thresholds: config.thresholds,
+            sequence_separation: config.sequence_separation,
+            skip_hetero: config.skip_hetero,
+            skip_water: config.skip_water,
+            skip_energy: config.skip_energy,
+            perform_msa: config.perform_msa,
+            ring_md: config.ring_md,
+            model: config.model,
+        }
+    }
+}
+
+impl From<Settings> for SettingsConfig {
+    fn from(settings: Settings) -> Self {
+        SettingsConfig {
+            chain: settings.chain,
+            network_policy: settings.network_policy,
+            interactions: settings.interactions,
+            thresholds: settings.thresholds,
+            sequence_separation: settings.sequence_separation,
+            skip_hetero: settings.skip_hetero,
+            skip_water: settings.skip_water,
+            skip_energy: settings.skip_energy,
+            perform_msa: settings.perform_msa,
+            ring_md: settings.ring_md,
+            model: settings.model,
+        }
+    }
+}
+
+/// Either the name of a threshold preset, or explicit thresholds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ThresholdsConfig {
+    /// The name of a preset, see `Thresholds::preset()`.
+    Preset(String),
+    /// Explicit thresholds.
+    Custom(CustomThresholds),
+}
+
+/// `Thresholds` with the names of the Rust fields instead of those of RING.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CustomThresholds {
+    /// See `Thresholds::hydrogen`.
+    hydrogen: f32,
+    /// See `Thresholds::van_der_waals`.
+    van_der_waals: f32,
+    /// See `Thresholds::ionic`.
+    ionic: f32,
+    /// See `Thresholds::pi_pi`.
+    pi_pi: f32,
+    /// See `Thresholds::pi_cation`.
+    pi_cation: f32,
+    /// See `Thresholds::disulphide`.
+    disulphide: f32,
+}
+
+impl Default for CustomThresholds {
+    fn default() -> Self {
+        Thresholds::default().into()
+    }
+}
+
+impl From<Thresholds> for CustomThresholds {
+    fn from(t: Thresholds) -> Self {
+        CustomThresholds {
+            hydrogen: t.hydrogen,
+            van_der_waals: t.van_der_waals,
+            ionic: t.ionic,
+            pi_pi: t.pi_pi,
+            pi_cation: t.pi_cation,
+            disulphide: t.disulphide,
+        }
+    }
+}
+
+impl From<CustomThresholds> for Thresholds {
+    fn from(t: CustomThresholds) -> Self {
+        Thresholds::new(t.hydrogen, t.van_der_waals, t.ionic, t.pi_pi, t.pi_cation, t.disulphide)
+    }
+}
+
+/// Serializes thresholds as the name of their preset, if any,
+/// and as a table otherwise.
+fn serialize_thresholds<S: Serializer>(thresholds: &Thresholds, serializer: S) -> Result<S::Ok, S::Error> {
+    match thresholds.preset_name() {
+        Some(name) => ThresholdsConfig::Preset(String::from(name)),
+        None => ThresholdsConfig::Custom((*thresholds).into()),
+    }.serialize(serializer)
+}
+
+/// Deserializes thresholds from either the name of a preset or a table.
+fn deserialize_thresholds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Thresholds, D::Error> {
+    match ThresholdsConfig::deserialize(deserializer)? {
+        ThresholdsConfig::Preset(name) => Thresholds::preset(&name).ok_or_else(|| D::Error::custom(
+            format!("unknown threshold preset `{}`; expected strict or relaxed", name)
+        )),
+        ThresholdsConfig::Custom(custom) => Ok(custom.into()),
+    }
+}
+
+/// Serializes a value as its `Display` representation.
+fn serialize_display<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// Deserializes a value by parsing a string with `FromStr`.
+fn deserialize_from_str<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where T: FromStr,
+          T::Err: Display,
+          D: Deserializer<'de>,
+{
+    String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+}
@@ -2,26 +2,57 @@
 
 use std::fmt::{ Display, Formatter, Result as FmtResult };
 use std::io::Error as IoError;
+use std::path::PathBuf;
 use std::error::Error as StdError;
 use std::char::ParseCharError;
 use std::num::{ ParseIntError, ParseFloatError };
 use serde::ser::Error as SerError;
 use serde_json::Error as JsonError;
-use reqwest::Error as ReqwestError;
+use reqwest::{ Error as ReqwestError, StatusCode };
+use crate::job::JobId;
 
 /// A RING API error.
 #[derive(Debug)]
 pub enum Error {
-    /// An HTTP error (either a network problem or a RING API error).
+    /// A network-level error, e.g. the server is unreachable.
     Reqwest(ReqwestError),
+    /// The server responded with a non-success HTTP status code.
+    Http {
+        /// The status code of the response.
+        status: StatusCode,
+        /// The body of the response, which usually describes the problem.
+        body: String,
+    },
     /// A serialization error.
     Serialization(String),
-    /// A parsing error.
-    Parsing(Box<dyn StdError + 'static>),
+    /// A parsing error. The cause is `Send + Sync`, like the other
+    /// causes, so that errors can be sent across threads.
+    Parsing(Box<dyn StdError + Send + Sync + 'static>),
     /// A JSON error.
     Json(JsonError),
     /// An I/O error.
     Io(IoError),
+    /// An I/O error while accessing a particular file.
+    File {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying I/O error.
+        cause: IoError,
+    },
+    /// Data is structurally valid but semantically inconsistent.
+    Validation(String),
+    /// The RING job failed on the server.
+    JobFailed {
+        /// The ID of the failed job.
+        job_id: JobId,
+        /// Why the job failed, if the server reported it.
+        reason: Option<String>,
+    },
+    /// Waiting for the RING job to finish took longer than allowed.
+    JobTimeout {
+        /// The ID of the job that is still running.
+        job_id: JobId,
+    },
 }
 
 impl Display for Error {
@@ -30,6 +61,12 @@ impl Display for Error {
             Error::Reqwest(ref cause) => write!(
                 formatter, "RING error: {}", cause
             ),
+            Error::Http { status, ref body } if body.trim().is_empty() => write!(
+                formatter, "HTTP error: {}", status
+            ),
+            Error::Http { status, ref body } => write!(
+                formatter, "HTTP error: {}: {}", status, body.trim()
+            ),
             Error::Serialization(ref message) => write!(
                 formatter, "serialization error: {}", message
             ),
@@ -42,6 +79,21 @@ impl Display for Error {
             Error::Io(ref cause) => write!(
                 formatter, "I/O error: {}", cause
             ),
+            Error::File { ref path, ref cause } => write!(
+                formatter, "I/O error on `{}`: {}", path.display(), cause
+            ),
+            Error::Validation(ref message) => write!(
+                formatter, "validation error: {}", message
+            ),
+            Error::JobFailed { ref job_id, reason: Some(ref reason) } => write!(
+                formatter, "job {} failed: {}", job_id, reason
+            ),
+            Error::JobFailed { ref job_id, reason: None } => write!(
+                formatter, "job {} failed", job_id
+            ),
+            Error::JobTimeout { ref job_id } => write!(
+                formatter, "timed out waiting for job {}", job_id
+            ),
         }
     }
 }
@@ -50,10 +102,15 @@ impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
             Error::Reqwest(ref cause) => Some(cause),
+            Error::Http { .. } => None,
             Error::Serialization(_) => None,
             Error::Parsing(ref cause) => Some(&**cause),
             Error::Json(ref cause) => Some(cause),
             Error::Io(ref cause) => Some(cause),
+            Error::File { ref cause, .. } => Some(cause),
+            Error::Validation(_) => None,
+            Error::JobFailed { .. } => None,
+            Error::JobTimeout { .. } => None,
         }
     }
 }
@@ -0,0 +1,508 @@
+//! Graph algorithms over, and exporting of, RING interaction networks.
+
+use std::cmp::Ordering;
+use std::fmt::{ Display, Formatter, Result as FmtResult, Write };
+use std::collections::{ HashMap, BTreeMap, BinaryHeap, VecDeque };
+use crate::requests::{ RetrieveResultResponse, Edge, NodeId, InteractionMainType };
+#[cfg(feature = "petgraph")]
+use std::collections::HashSet;
+#[cfg(feature = "petgraph")]
+use crate::requests::Node;
+#[cfg(feature = "petgraph")]
+use crate::error::{ Error, Result };
+
+/// How to assign a length (cost) to the edges of the interaction network
+/// when running weighted graph algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeWeight {
+    /// The `distance` of the edge, in Angstrom.
+    Distance,
+    /// The reciprocal of the `energy` of the edge, so that stronger
+    /// interactions correspond to shorter edges. Edges with a non-positive
+    /// energy are ignored.
+    InverseEnergy,
+}
+
+impl EdgeWeight {
+    /// Returns the length of an edge according to this weighting scheme,
+    /// or `None` if the edge can't be assigned a finite, non-negative length.
+    pub fn weight_of(&self, edge: &Edge) -> Option<f64> {
+        let weight = match *self {
+            EdgeWeight::Distance => edge.distance,
+            EdgeWeight::InverseEnergy if edge.energy > 0.0 => edge.energy.recip(),
+            EdgeWeight::InverseEnergy => return None,
+        };
+
+        if weight.is_finite() && weight >= 0.0 {
+            Some(weight)
+        } else {
+            None
+        }
+    }
+}
+
+/// What the entries of an adjacency matrix represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AdjacencyWeight {
+    /// 1 if there is at least one edge between two nodes, 0 otherwise.
+    Unit,
+    /// The number of edges between two nodes.
+    EdgeCount,
+    /// The sum of the energies of the edges between two nodes.
+    Energy,
+}
+
+impl RetrieveResultResponse {
+    /// Computes the normalized betweenness centrality of every node, using
+    /// Brandes' algorithm on the unweighted, undirected contact network.
+    ///
+    /// Scores are normalized by `(n - 1) * (n - 2) / 2`, the number of pairs
+    /// of other nodes, so they fall between 0 and 1. Edges referring to
+    /// nodes which are not in `nodes`, as well as self-loops, are ignored.
+    pub fn betweenness_centrality(&self) -> HashMap<NodeId, f64> {
+        Adjacency::new(self, None).betweenness_centrality()
+    }
+
+    /// Like `betweenness_centrality()`, but shortest paths are computed
+    /// with edge lengths given by `weight`. When there are several edges
+    /// between two nodes, the shortest one is used.
+    pub fn betweenness_centrality_weighted(&self, weight: EdgeWeight) -> HashMap<NodeId, f64> {
+        Adjacency::new(self, Some(weight)).betweenness_centrality()
+    }
+
+    /// Converts the interaction network into a `petgraph` graph, with a
+    /// copy of each node and edge as the weights.
+    ///
+    /// Vertices are in the same order as `nodes`. Each edge is directed from
+    /// `node_id_1` to `node_id_2`; edges referring to nodes which are not in
+    /// `nodes` are skipped, just like in the other graph algorithms. Use
+    /// `try_to_petgraph()` to reject such edges instead.
+    #[cfg(feature = "petgraph")]
+    pub fn to_petgraph(&self) -> petgraph::Graph<Node, Edge> {
+        let mut graph = petgraph::Graph::with_capacity(self.nodes.len(), self.edges.len());
+        let indices: HashMap<_, _> = self.nodes
+            .iter()
+            .map(|node| (node.node_id, graph.add_node(node.clone())))
+            .collect();
+
+        for edge in &self.edges {
+            if let (Some(&a), Some(&b)) = (indices.get(&edge.node_id_1), indices.get(&edge.node_id_2)) {
+                graph.add_edge(a, b, edge.clone());
+            }
+        }
+
+        graph
+    }
+
+    /// Like `to_petgraph()`, but fails with `Error::Validation` if an edge
+    /// refers to a node which is not in `nodes`, instead of skipping it.
+    #[cfg(feature = "petgraph")]
+    pub fn try_to_petgraph(&self) -> Result<petgraph::Graph<Node, Edge>> {
+        let known: HashSet<_> = self.nodes.iter().map(|node| node.node_id).collect();
+        let dangling = self.edges.iter().find(|edge| {
+            !known.contains(&edge.node_id_1) || !known.contains(&edge.node_id_2)
+        });
+
+        match dangling {
+            Some(edge) => Err(Error::Validation(format!(
+                "edge {} - {} refers to a node which is not in the result",
+                edge.node_id_1, edge.node_id_2
+            ))),
+            None => Ok(self.to_petgraph()),
+        }
+    }
+
+    /// Renders the interaction network as an undirected Graphviz graph.
+    ///
+    /// Vertices are identified by their node ID and labeled with the
+    /// residue and its position. There is one line per edge, colored
+    /// according to its main interaction type.
+    pub fn to_dot(&self) -> String {
+        /// Writing to a `String` never fails.
+        const INFALLIBLE: &str = "formatting into a String failed";
+
+        let mut dot = String::from("graph ring {\n");
+
+        for node in &self.nodes {
+            writeln!(
+                dot,
+                "    {} [label={}];",
+                DotQuoted(node.node_id),
+                DotQuoted(format_args!("{} {}", node.residue, node.position)),
+            ).expect(INFALLIBLE);
+        }
+
+        for edge in &self.edges {
+            writeln!(
+                dot,
+                "    {} -- {} [color={}];",
+                DotQuoted(edge.node_id_1),
+                DotQuoted(edge.node_id_2),
+                dot_color(edge.interaction.main_type),
+            ).expect(INFALLIBLE);
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The adjacency matrix of the network, as a dense, symmetric matrix.
+    /// See `sparse_adjacency_matrix()` for the details.
+    pub fn adjacency_matrix(&self, weight: AdjacencyWeight) -> (Vec<NodeId>, Vec<Vec<f64>>) {
+        let (ids, triplets) = self.sparse_adjacency_matrix(weight);
+        let mut matrix = vec![vec![0.0; ids.len()]; ids.len()];
+
+        for (i, j, value) in triplets {
+            matrix[i][j] = value;
+        }
+
+        (ids, matrix)
+    }
+
+    /// The adjacency matrix of the network, as `(row, column, value)`
+    /// triplets of its nonzero entries, sorted by row, then by column.
+    ///
+    /// Rows and columns are indexed by the returned node IDs, which are in
+    /// the order of `nodes`, so the ordering is stable across calls. The
+    /// matrix is symmetric, i.e. the direction of edges is ignored. A
+    /// self-loop contributes once to the diagonal. Edges referring to nodes
+    /// which are not in `nodes` are skipped. With `AdjacencyWeight::Energy`,
+    /// entries whose energies sum to exactly 0 are left out as well.
+    pub fn sparse_adjacency_matrix(
+        &self,
+        weight: AdjacencyWeight,
+    ) -> (Vec<NodeId>, Vec<(usize, usize, f64)>) {
+        let ids: Vec<_> = self.nodes.iter().map(|node| node.node_id).collect();
+        let mut indices = HashMap::with_capacity(ids.len());
+        let mut entries = BTreeMap::new();
+
+        for (i, &id) in ids.iter().enumerate() {
+            indices.entry(id).or_insert(i);
+        }
+
+        for edge in &self.edges {
+            if let (Some(&i), Some(&j)) = (indices.get(&edge.node_id_1), indices.get(&edge.node_id_2)) {
+                let contribution = match weight {
+                    AdjacencyWeight::Unit | AdjacencyWeight::EdgeCount => 1.0,
+                    AdjacencyWeight::Energy => edge.energy,
+                };
+
+                for &key in &[(i, j), (j, i)] {
+                    *entries.entry(key).or_insert(0.0) += contribution;
+
+                    if i == j {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let triplets = entries
+            .into_iter()
+            .filter(|&(_, value)| value != 0.0)
+            .map(|((i, j), value)| match weight {
+                AdjacencyWeight::Unit => (i, j, 1.0),
+                AdjacencyWeight::EdgeCount | AdjacencyWeight::Energy => (i, j, value),
+            })
+            .collect();
+
+        (ids, triplets)
+    }
+
+    /// Finds a shortest path between two residues, using BFS if `weight` is
+    /// `None`, or Dijkstra's algorithm with the given edge lengths otherwise.
+    ///
+    /// Returns the sequence of node IDs from `from` to `to` (both inclusive),
+    /// or `None` if either node is missing or they are not connected.
+    /// If there are several shortest paths, one of them is returned.
+    pub fn shortest_path(
+        &self,
+        from: &NodeId,
+        to: &NodeId,
+        weight: Option<EdgeWeight>,
+    ) -> Option<Vec<NodeId>> {
+        let adjacency = Adjacency::new(self, weight);
+        let source = adjacency.index_of(from)?;
+        let target = adjacency.index_of(to)?;
+        let paths = adjacency.shortest_paths(source);
+        let mut path = vec![adjacency.ids[target]];
+        let mut current = target;
+
+        while current != source {
+            current = *paths.predecessors[current].first()?;
+            path.push(adjacency.ids[current]);
+        }
+
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+/// Adjacency list representation of an interaction network.
+/// Nodes are identified by their index in the `nodes` vector of the result.
+#[derive(Debug, Clone)]
+struct Adjacency {
+    /// The IDs of the nodes, in the same order as in the result.
+    ids: Vec<NodeId>,
+    /// For each node, the index and the edge length of each of its neighbors.
+    neighbors: Vec<Vec<(usize, f64)>>,
+    /// Whether edge lengths are meaningful (otherwise they are all 1).
+    weighted: bool,
+}
+
+/// The result of a single-source shortest path search.
+#[derive(Debug, Clone)]
+struct ShortestPaths {
+    /// Nodes in order of non-decreasing distance from the source.
+    order: Vec<usize>,
+    /// The predecessors of each node on the shortest paths from the source.
+    predecessors: Vec<Vec<usize>>,
+    /// The number of shortest paths from the source to each node.
+    path_counts: Vec<f64>,
+}
+
+impl Adjacency {
+    /// Builds the adjacency list of the network. If `weight` is `None`,
+    /// every edge has unit length.
+    fn new(result: &RetrieveResultResponse, weight: Option<EdgeWeight>) -> Self {
+        let ids: Vec<_> = result.nodes.iter().map(|node| node.node_id).collect();
+        let indices: HashMap<_, _> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+        let mut neighbors = vec![Vec::new(); ids.len()];
+
+        for edge in &result.edges {
+            let endpoints = (indices.get(&edge.node_id_1), indices.get(&edge.node_id_2));
+            let maybe_length = match weight {
+                Some(w) => w.weight_of(edge),
+                None => Some(1.0),
+            };
+
+            if let ((Some(&i), Some(&j)), Some(length)) = (endpoints, maybe_length) {
+                if i != j {
+                    Self::insert_min(&mut neighbors[i], j, length);
+                    Self::insert_min(&mut neighbors[j], i, length);
+                }
+            }
+        }
+
+        Adjacency {
+            ids,
+            neighbors,
+            weighted: weight.is_some(),
+        }
+    }
+
+    /// Returns the index of the node with the given ID, if it exists.
+    fn index_of(&self, id: &NodeId) -> Option<usize> {
+        self.ids.iter().position(|other| other == id)
+    }
+
+    /// Adds a neighbor, or shortens the existing edge to it.
+    fn insert_min(list: &mut Vec<(usize, f64)>, node: usize, length: f64) {
+        match list.iter_mut().find(|&&mut (other, _)| other == node) {
+            Some(entry) => entry.1 = entry.1.min(length),
+            None => list.push((node, length)),
+        }
+    }
+
+    /// Runs a BFS (unweighted) or Dijkstra's algorithm (weighted)
+    /// from `source`, recording every shortest path.
+    fn shortest_paths(&self, source: usize) -> ShortestPaths {
+        if self.weighted {
+            self.dijkstra(source)
+        } else {
+            self.bfs(source)
+        }
+    }
+
+    /// Breadth-first search from `source`.
+    fn bfs(&self, source: usize) -> ShortestPaths {
+        let n = self.ids.len();
+        let mut order = Vec::with_capacity(n);
+        let mut predecessors = vec![Vec::new(); n];
+        let mut path_counts = vec![0.0; n];
+        let mut hops: Vec<Option<usize>> = vec![None; n];
+        let mut queue = VecDeque::new();
+
+        path_counts[source] = 1.0;
+        hops[source] = Some(0);
+        queue.push_back(source);
+
+        while let Some(v) = queue.pop_front() {
+            let next_hop = hops[v].map(|h| h + 1);
+            order.push(v);
+
+            for &(w, _) in &self.neighbors[v] {
+                if hops[w].is_none() {
+                    hops[w] = next_hop;
+                    queue.push_back(w);
+                }
+
+                if hops[w] == next_hop {
+                    path_counts[w] += path_counts[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+
+        ShortestPaths { order, predecessors, path_counts }
+    }
+
+    /// Dijkstra's algorithm from `source`. Path lengths which are equal up
+    /// to a small relative tolerance are considered to be equal.
+    fn dijkstra(&self, source: usize) -> ShortestPaths {
+        let n = self.ids.len();
+        let mut order = Vec::with_capacity(n);
+        let mut predecessors = vec![Vec::new(); n];
+        let mut path_counts = vec![0.0; n];
+        let mut distances: Vec<Option<f64>> = vec![None; n];
+        let mut settled = vec![false; n];
+        let mut heap = BinaryHeap::new();
+
+        path_counts[source] = 1.0;
+        distances[source] = Some(0.0);
+        heap.push(Candidate { distance: 0.0, node: source });
+
+        while let Some(Candidate { distance, node: v }) = heap.pop() {
+            if settled[v] {
+                continue;
+            }
+
+            settled[v] = true;
+            order.push(v);
+
+            for &(w, length) in &self.neighbors[v] {
+                if settled[w] {
+                    continue;
+                }
+
+                let candidate = distance + length;
+
+                match distances[w] {
+                    Some(current) if approx_eq(candidate, current) => {
+                        path_counts[w] += path_counts[v];
+                        predecessors[w].push(v);
+                    }
+                    Some(current) if current < candidate => {}
+                    _ => {
+                        distances[w] = Some(candidate);
+                        path_counts[w] = path_counts[v];
+                        predecessors[w].clear();
+                        predecessors[w].push(v);
+                        heap.push(Candidate { distance: candidate, node: w });
+                    }
+                }
+            }
+        }
+
+        ShortestPaths { order, predecessors, path_counts }
+    }
+
+    /// Brandes' algorithm for betweenness centrality.
+    #[allow(clippy::cast_precision_loss)]
+    fn betweenness_centrality(&self) -> HashMap<NodeId, f64> {
+        let n = self.ids.len();
+        let mut centrality = vec![0.0; n];
+
+        for source in 0..n {
+            let paths = self.shortest_paths(source);
+            let mut dependency = vec![0.0; n];
+
+            for &w in paths.order.iter().rev() {
+                for &v in &paths.predecessors[w] {
+                    dependency[v] += paths.path_counts[v] / paths.path_counts[w]
+                                     * (1.0 + dependency[w]);
+                }
+
+                if w != source {
+                    centrality[w] += dependency[w];
+                }
+            }
+        }
+
+        // Every pair is counted twice in an undirected graph, which cancels
+        // out with the factor 2 in the denominator of the normalization.
+        let scale = if n > 2 {
+            ((n - 1) * (n - 2)) as f64
+        } else {
+            0.0
+        };
+
+        self.ids
+            .iter()
+            .zip(centrality)
+            .map(|(&id, c)| (id, if scale > 0.0 { c / scale } else { 0.0 }))
+            .collect()
+    }
+}
+
+/// An entry in the priority queue of Dijkstra's algorithm.
+/// Ordered so that `BinaryHeap` pops the closest node first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    /// The tentative distance from the source.
+    distance: f64,
+    /// The index of the node.
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance
+            .partial_cmp(&self.distance)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+/// Formats a value as a quoted and escaped DOT identifier.
+#[derive(Debug, Clone, Copy)]
+struct DotQuoted<T>(T);
+
+impl<T: Display> Display for DotQuoted<T> {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        /// Escapes backslashes and double quotes on the fly.
+        struct Escaper<'a, 'b>(&'a mut Formatter<'b>);
+
+        impl Write for Escaper<'_, '_> {
+            fn write_str(&mut self, s: &str) -> FmtResult {
+                for c in s.chars() {
+                    if c == '\\' || c == '"' {
+                        self.0.write_char('\\')?;
+                    }
+                    self.0.write_char(c)?;
+                }
+                Ok(())
+            }
+        }
+
+        formatter.write_char('"')?;
+        write!(Escaper(formatter), "{}", self.0)?;
+        formatter.write_char('"')
+    }
+}
+
+/// The Graphviz color of edges of the given interaction type.
+fn dot_color(main_type: InteractionMainType) -> &'static str {
+    match main_type {
+        InteractionMainType::HydrogenBond => "blue",
+        InteractionMainType::VanDerWaals  => "gray",
+        InteractionMainType::Disulphide   => "gold",
+        InteractionMainType::Ionic        => "red",
+        InteractionMainType::PiPiStack    => "orange",
+        InteractionMainType::PiCation     => "darkgreen",
+    }
+}
+
+/// Compares two path lengths with a small relative tolerance.
+fn approx_eq(x: f64, y: f64) -> bool {
+    (x - y).abs() <= 1e-9 * x.abs().max(y.abs()).max(1.0)
+}
@@ -1,6 +1,7 @@
 //! Types for manipulating jobs.
 
 use std::fmt::{ Display, Formatter, Result as FmtResult };
+use crate::error::{ Error, Result };
 
 /// This is received in responses from the RING server.
 /// Indicates what phase a specific job is currently in.
@@ -22,6 +23,24 @@ pub enum JobStatus {
     Failed,
 }
 
+impl JobStatus {
+    /// Returns `true` if the job has completed successfully.
+    pub fn is_complete(&self) -> bool {
+        *self == JobStatus::Complete
+    }
+
+    /// Returns `true` if the job has failed.
+    pub fn is_failed(&self) -> bool {
+        *self == JobStatus::Failed
+    }
+
+    /// Returns `true` if the job won't make any more progress, i.e. it is
+    /// either complete or failed. Note that `Partial` is not terminal.
+    pub fn is_terminal(&self) -> bool {
+        self.is_complete() || self.is_failed()
+    }
+}
+
 impl Display for JobStatus {
     fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
         formatter.pad(match *self {
@@ -34,22 +53,68 @@ impl Display for JobStatus {
 }
 
 /// A RING Job ID.
+///
+/// Job IDs must not be empty: requests for an empty job ID would be sent to
+/// the wrong endpoint (e.g. `/status/` instead of `/status/<id>`). `new()`
+/// rejects them, and building the endpoint of a request for an empty job ID
+/// panics in debug builds.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct JobId(String);
 
 impl JobId {
+    /// The number of hexadecimal digits in a well-formed job ID.
+    pub const LEN: usize = 24;
+
+    /// Creates a job ID, checking that it looks like one issued by RING,
+    /// i.e. that it consists of exactly 24 hexadecimal digits.
+    ///
+    /// The `From` conversions don't validate the ID, so they can still be
+    /// used for job IDs that don't follow this format.
+    pub fn new<T: Into<String>>(id: T) -> Result<Self> {
+        let job_id = JobId(id.into());
+
+        if job_id.is_empty() {
+            Err(Error::Validation(String::from("job ID is empty")))
+        } else if job_id.is_valid() {
+            Ok(job_id)
+        } else {
+            Err(Error::Validation(format!(
+                "job ID `{}` is not {} hexadecimal digits", job_id, Self::LEN
+            )))
+        }
+    }
+
+    /// Returns `true` if this job ID consists of exactly 24 hex digits.
+    pub fn is_valid(&self) -> bool {
+        self.0.len() == Self::LEN && self.0.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+
+    /// Returns `true` if the job ID is the empty string.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Formats the endpoint `/<resource>/<job ID>` of a request for this job.
+    /// Panics in debug builds if the job ID is empty.
+    pub(crate) fn endpoint(&self, resource: &str) -> String {
+        debug_assert!(!self.is_empty(), "empty job ID in request for `/{}`", resource);
+        format!("/{}/{}", resource, self)
+    }
+
     /// Returns the string representation of the job ID.
     pub fn as_str(&self) -> &str {
         &self.0
     }
 }
 
+/// The caller must ensure that the string is not empty; see `JobId`.
 impl From<String> for JobId {
     fn from(string: String) -> Self {
         JobId(string)
     }
 }
 
+/// The caller must ensure that the string is not empty; see `JobId`.
 impl From<&str> for JobId {
     fn from(string: &str) -> Self {
         JobId(string.into())
@@ -10,14 +10,15 @@
         variant_size_differences,
         unused_import_braces, unused_qualifications, missing_docs)]
 #![allow(clippy::single_match, clippy::match_same_arms, clippy::match_ref_pats,
-         clippy::clone_on_ref_ptr, clippy::needless_pass_by_value)]
-#![deny(clippy::wrong_pub_self_convention, clippy::used_underscore_binding,
-        clippy::similar_names, clippy::pub_enum_variant_names,
+         clippy::clone_on_ref_ptr, clippy::needless_pass_by_value,
+         clippy::derivable_impls)]
+#![deny(clippy::used_underscore_binding,
+        clippy::similar_names,
         clippy::missing_docs_in_private_items,
         clippy::non_ascii_literal, clippy::unicode_not_nfc,
-        clippy::result_unwrap_used, clippy::option_unwrap_used,
-        clippy::option_map_unwrap_or_else, clippy::option_map_unwrap_or,
-        clippy::filter_map,
+        clippy::unwrap_used,
+        clippy::map_unwrap_or,
+        clippy::manual_filter_map,
         clippy::shadow_unrelated, clippy::shadow_reuse, clippy::shadow_same,
         clippy::int_plus_one, clippy::string_add_assign, clippy::if_not_else,
         clippy::invalid_upcast_comparisons,
@@ -31,19 +32,36 @@ extern crate serde_derive;
 extern crate serde;
 extern crate serde_json;
 extern crate reqwest;
+extern crate http;
+#[macro_use]
+extern crate log;
+#[cfg(feature = "petgraph")]
+extern crate petgraph;
+#[cfg(feature = "gzip-files")]
+extern crate flate2;
+#[cfg(feature = "yaml")]
+extern crate serde_yaml;
+#[cfg(feature = "toml")]
+extern crate toml_crate as toml;
 
 pub use client::*;
 pub use error::*;
 pub use requests::*;
 pub use settings::*;
 pub use job::*;
+pub use graph::*;
+pub use transport::*;
+pub use config::*;
 
 pub mod client;
 pub mod error;
 pub mod requests;
 pub mod settings;
 pub mod job;
+pub mod graph;
 pub mod multipart;
+pub mod transport;
+pub mod config;
 
 #[cfg(test)]
 #[allow(clippy::print_stdout)]
@@ -61,7 +79,7 @@ mod tests {
         let request = SubmitId {
             pdb_id: String::from("3S6A"),
             settings: Settings {
-                chain: Chain::Id('A'),
+                chain: Chain::Id("A".parse()?),
                 network_policy: NetworkPolicy::CAlpha,
                 interactions: InteractionType::All,
                 ..Default::default()
@@ -71,14 +89,14 @@ mod tests {
         println!("{:#?}", response);
 
         loop {
-            let request = Status {
+            let status_request = Status {
                 job_id: response.job_id.clone(),
             };
-            let response = client.send(&request)?;
+            let status_response = client.send(&status_request)?;
 
-            println!("{:#?}", response);
+            println!("{:#?}", status_response);
 
-            match response.status {
+            match status_response.status {
                 JobStatus::Complete => break Ok(()),
                 JobStatus::Failed => panic!("job failed"),
                 JobStatus::InProgress | JobStatus::Partial => {}
@@ -101,14 +119,14 @@ mod tests {
         println!("{:#?}", response);
 
         loop {
-            let request = Status {
+            let status_request = Status {
                 job_id: response.job_id.clone(),
             };
-            let response = client.send(&request)?;
+            let status_response = client.send(&status_request)?;
 
-            println!("{:#?}", response);
+            println!("{:#?}", status_response);
 
-            match response.status {
+            match status_response.status {
                 JobStatus::Complete => break Ok(()),
                 JobStatus::Failed => panic!("job failed"),
                 JobStatus::InProgress | JobStatus::Partial => {}
@@ -118,10 +136,133 @@ mod tests {
         }
     }
 
+    #[test]
+    fn multipart_file_contents() -> Result<()> {
+        let text = SubmitStructure::with_pdb_structure("HEADER    TEST");
+        let bytes = SubmitStructure::with_bytes_slice(b"REMARK \xff\xfe", "binary.pdb");
+
+        assert_eq!(bytes.file_name.as_deref(), Some("binary.pdb"));
+        assert_eq!(bytes.pdb_structure.contents().as_str(), None);
+        assert_eq!(text.pdb_structure.contents().as_bytes(), Some(&b"HEADER    TEST"[..]));
+
+        multipart::to_form(&text)?;
+        multipart::to_form(&bytes)?;
+
+        let streamed = SubmitStructure::with_streamed_file("testdata/3s6a.pdb");
+        let missing = SubmitStructure::with_streamed_file("testdata/missing.pdb");
+
+        assert_eq!(streamed.file_name.as_deref(), Some("3s6a.pdb"));
+        assert_eq!(streamed.pdb_structure.contents().as_bytes(), None);
+        multipart::to_form(&streamed)?;
+        assert!(multipart::to_form(&missing).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn retry_policy() {
+        use std::time::Duration;
+        use reqwest::Method;
+
+        let policy = RetryPolicy::new(3, Duration::from_millis(100));
+
+        assert_eq!(policy.delay_for(0), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(1), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(800));
+        assert!(policy.allows(&Method::GET));
+        assert!(!policy.allows(&Method::POST));
+        assert!(policy.retry_non_idempotent(true).allows(&Method::POST));
+        assert_eq!(policy.delay_for(40), RetryPolicy::DEFAULT_MAX_DELAY);
+        assert_eq!(policy.max_delay(Duration::from_millis(300)).delay_for(2), Duration::from_millis(300));
+        assert_eq!(RetryPolicy::default(), RetryPolicy::never());
+    }
+
+    #[test]
+    fn response_meta() {
+        use std::time::Duration;
+        use reqwest::{ StatusCode, header::{ HeaderMap, HeaderValue, RETRY_AFTER } };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("7"));
+
+        let meta = ResponseMeta { status: StatusCode::TOO_MANY_REQUESTS, headers };
+
+        assert_eq!(meta.retry_after(), Some(Duration::from_secs(120)));
+        assert_eq!(meta.rate_limit_remaining(), Some(7));
+        assert!(!meta.is_not_modified());
+
+        let cached = ResponseMeta { status: StatusCode::NOT_MODIFIED, headers: HeaderMap::new() };
+        assert!(cached.is_not_modified());
+    }
+
+    #[test]
+    fn settings_config() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let config: SettingsConfig = serde_json::from_str(r#"{
+            "chain": "A",
+            "network_policy": "cb",
+            "interactions": "most_energetic",
+            "thresholds": { "hydrogen": 4.0, "pi_pi": 7.0 },
+            "sequence_separation": 5
+        }"#)?;
+        let settings = Settings::from(config);
+
+        assert_eq!(settings.chain, Chain::Id("A".parse()?));
+        assert_eq!(settings.network_policy, NetworkPolicy::CBeta);
+        assert_eq!(settings.interactions, InteractionType::MostEnergetic);
+        assert_eq!(settings.thresholds, Thresholds { hydrogen: 4.0, pi_pi: 7.0, ..Thresholds::strict() });
+        assert_eq!(settings.sequence_separation, 5);
+        assert!(settings.skip_water);
+        assert_eq!(SettingsConfig::from(settings), config);
+
+        let preset = serde_json::to_value(SettingsConfig::default())?;
+        assert_eq!(preset["thresholds"], "strict");
+        assert!(serde_json::from_str::<SettingsConfig>(r#"{ "seqSeparation": 5 }"#).is_err());
+        assert!(serde_json::from_str::<SettingsConfig>(r#"{ "thresholds": "loose" }"#).is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn settings_config_yaml() -> Result<()> {
+        let config = SettingsConfig::from_yaml_str("chain: B\nthresholds: relaxed\nmodel: 3\n")?;
+
+        assert_eq!(config.thresholds, Thresholds::relaxed());
+        assert_eq!(config.model, Some(3));
+        assert_eq!(SettingsConfig::from_yaml_str(&config.to_yaml_string()?)?, config);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn settings_config_toml() -> Result<()> {
+        let config = SettingsConfig::from_toml_str("chain = \"B\"\n[thresholds]\nionic = 4.5\n")?;
+
+        assert_eq!(config.thresholds.ionic, 4.5);
+        assert_eq!(SettingsConfig::from_toml_str(&config.to_toml_string()?)?, config);
+
+        Ok(())
+    }
+
+    #[test]
+    fn threshold_sweep() {
+        let base = Thresholds::strict();
+        let sweep: Vec<_> = base.sweep(ThresholdField::Ionic, &[3.0, 4.5]).collect();
+
+        assert_eq!(sweep.len(), 2);
+        assert_eq!(sweep[0].get(ThresholdField::Ionic), 3.0);
+        assert_eq!(sweep[1], Thresholds { ionic: 4.5, ..base });
+        assert_eq!(base.preset_name(), Some("strict"));
+        assert_eq!(sweep[1].preset_name(), None);
+        assert_eq!(Thresholds::preset("relaxed"), Some(Thresholds::relaxed()));
+    }
+
     #[test]
     fn serde() -> std::result::Result<(), Box<dyn std::error::Error>> {
         let s1 = Settings {
-            chain: Chain::Id('X'),
+            chain: Chain::Id("XY".parse()?),
             network_policy: NetworkPolicy::CBeta,
             interactions: InteractionType::NoSpecific,
             sequence_separation: 42,
@@ -129,15 +270,1545 @@ mod tests {
             skip_hetero: true,
             skip_water: false,
             perform_msa: true,
+            ring_md: true,
+            model: Some(2),
             ..Default::default()
         };
         let json = serde_json::to_string_pretty(&s1)?;
         let s2: Settings = serde_json::from_str(&json)?;
-        assert_eq!(s1, s2);
+        assert!(s1.approx_eq(&s2, 1e-6));
 
         let json_default = serde_json::to_string(&Settings::default())?;
         let s_default: Settings = serde_json::from_str(&json_default)?;
-        assert_eq!(s_default, Settings::default());
+        assert!(s_default.approx_eq(&Settings::default(), 1e-6));
+        assert!(!s_default.approx_eq(&s1, 1e-6));
+        assert!(Thresholds::uniform(1.0).approx_eq(&Thresholds::uniform(1.0 + 1e-7), 1e-6));
+
+        Ok(())
+    }
+
+    #[test]
+    fn serde_ring_md() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let settings = Settings { ring_md: true, ..Default::default() };
+
+        assert!(serde_json::to_string(&settings)?.contains(r#""ringmd":"true""#));
+        assert!(serde_json::to_string(&Settings::default())?.contains(r#""ringmd":"false""#));
+
+        Ok(())
+    }
+
+    #[test]
+    fn serde_model() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let settings = Settings { model: Some(2), ..Default::default() };
+        let json = serde_json::to_string(&settings)?;
+
+        assert!(json.contains(r#""model":"2""#));
+        assert_eq!(serde_json::from_str::<Settings>(&json)?.model, Some(2));
+        assert!(!serde_json::to_string(&Settings::default())?.contains("model"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn serde_msa() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        assert!(serde_json::to_string(&Settings::default())?.contains(r#""msa":"false""#));
+        assert!(serde_json::from_str::<Settings>(r#"{ "msa": "1" }"#)?.perform_msa);
+        assert!(serde_json::from_str::<Settings>(r#"{ "msa": "true" }"#)?.perform_msa);
+        assert!(!serde_json::from_str::<Settings>(r#"{ "msa": "false" }"#)?.perform_msa);
+        assert!(!serde_json::from_str::<Settings>(r#"{ "msa": "0" }"#)?.perform_msa);
+        assert!(serde_json::from_str::<Settings>(r#"{ "msa": "off" }"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn serde_threshold_presets() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let relaxed = Settings { thresholds: Thresholds::relaxed(), ..Default::default() };
+        let relaxed_json = serde_json::to_string(&relaxed)?;
+        assert!(relaxed_json.contains(r#""thresholds":"relaxed""#));
+        assert_eq!(serde_json::from_str::<Settings>(&relaxed_json)?.thresholds, Thresholds::relaxed());
+
+        let custom = Settings { thresholds: Thresholds::uniform(2.0), ..Default::default() };
+        let custom_json = serde_json::to_string(&custom)?;
+        assert!(custom_json.contains(r#""thresholds":"{"#));
+        assert!(serde_json::from_str::<Settings>(&custom_json)?.approx_eq(&custom, 1e-6));
+
+        Ok(())
+    }
+
+    #[test]
+    fn chain_id() -> Result<()> {
+        assert_eq!("BC".parse::<ChainId>()?.as_str(), "BC");
+        assert!("ABCDE".parse::<ChainId>().is_err());
+        assert!("".parse::<ChainId>().is_err());
+        assert!("*".parse::<ChainId>().is_err());
+        assert!("A:".parse::<ChainId>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn chain() -> Result<()> {
+        assert_eq!("all".parse::<Chain>()?, Chain::All);
+        assert_eq!("AB".parse::<Chain>()?, Chain::Id("AB".parse()?));
+        assert_eq!(Chain::All.to_string(), "all");
+        assert_eq!(Chain::Id("X".parse()?).to_string(), "X");
+        assert_eq!(serde_json::from_str::<Chain>(r#""B""#)?, Chain::Id("B".parse()?));
+        assert!("A*".parse::<Chain>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn chain_aliases() -> Result<()> {
+        for &alias in &["all", "ALL", "All", "*", ""] {
+            assert_eq!(alias.parse::<Chain>()?, Chain::All);
+            assert_eq!(serde_json::from_str::<Chain>(&format!("{:?}", alias))?, Chain::All);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn node_id_parsing() -> Result<()> {
+        let id: NodeId = "BC:-3:_:ALA".parse()?;
+        assert_eq!(id.chain_id.as_str(), "BC");
+        assert_eq!(id.to_string(), "BC:-3:_:ALA");
+        assert!(!id.has_insertion_code());
+
+        for &s in &["A:100::ALA", "A:-5:A:GLY", "A:7:_:SER"] {
+            assert_eq!(s.parse::<NodeId>()?.to_string(), s);
+        }
+        assert_eq!("A:100::ALA".parse::<NodeId>()?.insertion_code, NodeId::BLANK_INSERTION_CODE);
+        assert!("A:-5:A:GLY".parse::<NodeId>()?.has_insertion_code());
+
+        Ok(())
+    }
+
+    #[test]
+    fn residue_one_letter() {
+        for c in "ARNDCEQGHILKMFPSTWYV".chars() {
+            let residue = Residue::from_one_letter(c).expect("standard residue");
+            assert_eq!(residue.one_letter(), c);
+        }
+
+        assert_eq!(Residue::from_one_letter('w'), Some(Residue::Tryptophan));
+        assert_eq!(Residue::from_one_letter('X'), Some(Residue::Unknown));
+        assert_eq!(Residue::from_one_letter('1'), None);
+        assert_eq!(Residue::Ornithine.one_letter(), 'X');
+        assert_eq!(Residue::GlutamineOrGlutamicAcid.one_letter(), 'Z');
+        assert_eq!(Residue::AsparticAcid.full_name(), "Aspartic acid");
+        assert_eq!(Residue::AsparticAcid.three_letter(), "ASP");
+        assert_eq!(serde_json::to_value(Residue::Unknown).ok(), Some("XAA".into()));
+    }
+
+    #[test]
+    fn residue_properties() {
+        assert!(Residue::Leucine.is_hydrophobic());
+        assert!(!Residue::Serine.is_hydrophobic());
+        assert!(Residue::Tryptophan.is_aromatic());
+        assert_eq!(Residue::Lysine.charge_at_ph7(), 1);
+        assert_eq!(Residue::GlutamicAcid.charge_at_ph7(), -1);
+        assert!(!Residue::Histidine.is_charged());
+        assert!(!Residue::GlutamineOrGlutamicAcid.is_charged());
+        assert_eq!(Residue::Glycine.molecular_weight(), Some(75.07));
+        assert_eq!(Residue::AsparagineOrAsparticAcid.molecular_weight(), None);
+        assert_eq!(Residue::Unknown.molecular_weight(), None);
+
+        assert_eq!(Residue::all().iter().filter(|residue| residue.is_standard()).count(), 20);
+        assert!(Residue::all().iter().all(|residue| {
+            let canonical = residue.canonicalize();
+            canonical.is_standard() || canonical == Residue::Unknown
+        }));
+        assert_eq!(Residue::Selenocysteine.canonicalize(), Residue::Cysteine);
+        assert_eq!(Residue::Tyrosine.canonicalize(), Residue::Tyrosine);
+        assert_eq!(Residue::LeucineOrIsoleucine.canonicalize(), Residue::Unknown);
+    }
+
+    #[test]
+    fn dssp_structure_round_trip() -> Result<()> {
+        assert_eq!(DsspStructure::None.to_string(), " ");
+        assert_eq!(" ".parse::<DsspStructure>()?, DsspStructure::None);
+        assert_eq!(DsspStructure::HelixAlpha.to_string(), "H");
+        assert_eq!("E".parse::<DsspStructure>()?, DsspStructure::BetaExtended);
+        assert!("Q".parse::<DsspStructure>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn display_matches_serde() -> Result<()> {
+        for residue in Residue::all() {
+            assert_eq!(serde_json::to_value(residue)?, residue.to_string().as_str());
+        }
+        for main_type in InteractionMainType::all() {
+            assert_eq!(serde_json::to_value(main_type)?, main_type.to_string().as_str());
+        }
+        for &subtype in InteractionSubType::all() {
+            assert_eq!(serde_json::to_value(subtype)?, subtype.to_string().as_str());
+
+            for &main_type in InteractionMainType::all() {
+                let interaction = Interaction { main_type, subtype_1: subtype, subtype_2: subtype };
+                let expected = format!("{}:{}_{}", main_type, subtype, subtype);
+
+                assert_eq!(interaction.to_string(), expected);
+                assert_eq!(serde_json::to_value(interaction)?, expected.as_str());
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn decode_responses() -> Result<()> {
+        let json = reqwest::Response::from(http::Response::new(r#"{"version":"2.0"}"#));
+        let text = reqwest::Response::from(http::Response::new("HEADER    PROTEIN\n"));
+
+        assert_eq!(ServerVersion::decode(json)?.version.as_deref(), Some("2.0"));
+        assert_eq!(DownloadStructure::decode(text)?, "HEADER    PROTEIN\n");
+        assert_eq!(CancelJob::decode(reqwest::Response::from(http::Response::new("")))?, ());
+        assert_eq!(CancelJob::METHOD, reqwest::Method::DELETE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn interaction_type_round_trip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        for &interactions in InteractionType::all() {
+            let settings = Settings { interactions, ..Default::default() };
+            let json = serde_json::to_string(&settings)?;
+            let decoded: Settings = serde_json::from_str(&json)?;
+            assert_eq!(decoded.interactions, interactions);
+        }
+
+        let mixed: Settings = serde_json::from_str(
+            r#"{ "nospecific": "true", "allEdges": "true", "onlyFirstEdge": "true" }"#
+        )?;
+        assert_eq!(mixed.interactions, InteractionType::NoSpecific);
+
+        let first_and_all: Settings = serde_json::from_str(
+            r#"{ "allEdges": "true", "onlyFirstEdge": "true" }"#
+        )?;
+        assert_eq!(first_and_all.interactions, InteractionType::MostEnergetic);
+
+        for &network_policy in NetworkPolicy::all() {
+            let settings = Settings { network_policy, ..Default::default() };
+            let decoded: Settings = serde_json::from_str(&serde_json::to_string(&settings)?)?;
+            assert_eq!(decoded.network_policy, network_policy);
+            assert_eq!(network_policy.to_string().parse::<NetworkPolicy>()?, network_policy);
+            assert_eq!(serde_json::to_value(network_policy)?, network_policy.as_str());
+        }
+
+        for &interactions in InteractionType::all() {
+            assert_eq!(interactions.to_string().parse::<InteractionType>()?, interactions);
+        }
+        assert_eq!("most_energetic".parse::<InteractionType>()?, InteractionType::MostEnergetic);
+        assert!("CA".parse::<NetworkPolicy>().is_err());
+
+        let disabled: Settings = serde_json::from_str(r#"{ "allEdges": "false" }"#)?;
+        assert_eq!(disabled.interactions, InteractionType::Multiple);
+
+        Ok(())
+    }
+
+    #[test]
+    fn threshold_presets() {
+        let strict = Thresholds::strict();
+        let relaxed = Thresholds::relaxed();
+        let halfway = Thresholds::lerp(strict, relaxed, 0.5);
+
+        assert_eq!(Thresholds::new(3.5, 0.5, 4.0, 6.5, 5.0, 2.5), strict);
+        assert_eq!(Thresholds::uniform(1.0), strict);
+        assert_eq!(Thresholds::uniform(2.0).hydrogen, 7.0);
+        assert_eq!(Thresholds::lerp(strict, relaxed, 0.0), strict);
+        assert_eq!(Thresholds::lerp(strict, relaxed, 1.0), relaxed);
+        assert_eq!(halfway.hydrogen, 4.5);
+        assert_eq!(halfway.pi_cation, 6.0);
+    }
+
+    #[cfg(feature = "gzip-files")]
+    #[test]
+    fn gzip_pdb_file() -> Result<()> {
+        use std::io::Write;
+        use flate2::{ Compression, write::GzEncoder };
+
+        let plain = SubmitStructure::with_pdb_file("testdata/3s6a.pdb")?;
+        let path = std::env::temp_dir().join("ring_api_test_3s6a.pdb.gz");
+        let mut encoder = GzEncoder::new(std::fs::File::create(&path)?, Compression::default());
+
+        encoder.write_all(plain.pdb_structure.contents().as_bytes().expect("in-memory file"))?;
+        encoder.finish()?;
+
+        let gzipped = SubmitStructure::with_gzip_pdb_file(&path)?;
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(gzipped.file_name.as_deref(), Some("ring_api_test_3s6a.pdb"));
+        assert_eq!(gzipped.pdb_structure.contents(), plain.pdb_structure.contents());
+
+        Ok(())
+    }
+
+    #[test]
+    fn structure_format() {
+        let cif = SubmitStructure::with_structure("data_3S6A\n", StructureFormat::Cif);
+        let pdb = SubmitStructure::with_pdb_structure("HEADER\n");
+
+        assert_eq!(cif.pdb_structure.file_name(), "rust_ring_api_dummy.cif");
+        assert_eq!(pdb.pdb_structure.file_name(), "rust_ring_api_dummy.pdb");
+        assert_eq!(cif.file_name, None);
+        assert_eq!(StructureFormat::default(), StructureFormat::Pdb);
+
+        let bytes = SubmitStructure::with_bytes(b"REMARK \xff\n".to_vec());
+        assert_eq!(bytes.pdb_structure.file_name(), "rust_ring_api_dummy.pdb");
+        assert_eq!(bytes.pdb_structure.contents().as_bytes(), Some(&b"REMARK \xff\n"[..]));
+
+        match SubmitStructure::with_pdb_file("no/such/file.pdb") {
+            Err(Error::File { ref path, .. }) => assert!(path.ends_with("file.pdb")),
+            other => panic!("expected a file error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn user_agent_header() -> Result<()> {
+        /// Records the `User-Agent` header of every request.
+        #[derive(Debug, Default)]
+        struct UserAgents(std::sync::Mutex<Vec<Option<String>>>);
+
+        impl Transport for UserAgents {
+            fn execute(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+                let user_agent = request.headers()
+                    .get(reqwest::header::USER_AGENT)
+                    .map(|value| value.to_str().expect("non-ASCII User-Agent").to_owned());
+                self.0.lock().expect("poisoned lock").push(user_agent);
+                MockTransport::new().execute(request)
+            }
+        }
+
+        let recorded = std::sync::Arc::new(UserAgents::default());
+        let status = || Status { job_id: JobId::from("x") };
+
+        assert!(Client::with_transport(recorded.clone()).send(status()).is_err());
+        assert!(Client::builder()
+            .user_agent("my-pipeline/1.0")
+            .transport(recorded.clone())
+            .build()?
+            .send(status())
+            .is_err());
+
+        let user_agents = recorded.0.lock().expect("poisoned lock").clone();
+        assert_eq!(user_agents[0], Some(format!("ring_api/{}", env!("CARGO_PKG_VERSION"))));
+        assert_eq!(user_agents[1].as_deref(), Some("my-pipeline/1.0"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn client_headers() {
+        assert!(Client::with_user_agent("my-pipeline/1.0").is_ok());
+        assert!(Client::with_user_agent("line\nbreak").is_err());
+        assert!(Client::builder().user_agent("line\nbreak").build().is_err());
+        assert!(Client::with_auth_token("s3cr3t").is_ok());
+        assert!(Client::with_auth_token("s3cr3t\n").is_err());
+        assert!(Client::with_base_url("https://ring.example.org/ringws/").is_ok());
+        assert!(Client::with_base_url("ftp://ring.example.org").is_err());
+        assert!(Client::with_base_url("not a url").is_err());
+    }
+
+    #[test]
+    fn clones_share_configuration() -> Result<()> {
+        let client = Client::builder()
+            .base_url("https://ring.example.org/ringws/")
+            .response_cache(true)
+            .build()?;
+        let clone = client.clone();
+        let job_id = JobId::from("5cefd030b265bd294b0f6b2c");
+
+        client.set_tag(job_id.clone(), "wild type");
+        clone.set_auth_token("s3cr3t")?;
+
+        assert_eq!(clone.base_url(), "https://ring.example.org/ringws");
+        assert_eq!(clone.tag_of(&job_id).as_deref(), Some("wild type"));
+
+        // The token set on the clone is visible, but redacted, in the original.
+        let debug = format!("{:?}", client);
+        assert!(debug.contains("Sensitive") && !debug.contains("s3cr3t"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn failure_reason() -> Result<()> {
+        let failed: StatusResponse = serde_json::from_str(
+            r#"{ "_id": "x", "status": "error", "error": "no such chain: Q" }"#
+        )?;
+        let complete: StatusResponse = serde_json::from_str(
+            r#"{ "_id": "x", "status": "complete", "message": "done" }"#
+        )?;
+
+        let both: StatusResponse = serde_json::from_str(
+            r#"{ "_id": "x", "status": "error", "message": "oops", "error": "bad file" }"#
+        )?;
+
+        assert_eq!(failed.failure_reason(), Some("no such chain: Q"));
+        assert_eq!(complete.failure_reason(), None);
+        assert_eq!(both.failure_reason(), Some("bad file"));
+        assert_eq!(serde_json::to_value(&both)?["error"], "bad file");
+        assert_eq!(
+            Error::JobFailed { job_id: failed.job_id, reason: failed.error_message }.to_string(),
+            "job x failed: no such chain: Q"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn pdb_id_validation() -> Result<()> {
+        assert_eq!(SubmitId::with_validated_pdb_id("3s6a")?.pdb_id, "3S6A");
+        assert_eq!(SubmitId::with_validated_pdb_id("pdb_00003s6a")?.pdb_id, "PDB_00003S6A");
+        assert!(SubmitId::with_validated_pdb_id("0abc").is_err());
+        assert!(SubmitId::with_validated_pdb_id("abcd").is_err());
+        assert!(SubmitId::with_validated_pdb_id("3s6a5").is_err());
+        assert!(SubmitId::with_validated_pdb_id("pdb_0000-s6a").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn submit_id_query() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let request = SubmitIdQuery::from(SubmitId::with_pdb_id("2ace"));
+        let body = match request.body() {
+            RequestBody::Query(body) => body,
+            _ => panic!("query-string submission must use a query body"),
+        };
+        let http_request = reqwest::Client::new()
+            .get("http://localhost/submit")
+            .query(body)
+            .build()?;
+        let keys: Vec<_> = http_request.url().query_pairs().map(|(k, _)| k.into_owned()).collect();
+
+        assert_eq!(SubmitIdQuery::METHOD, reqwest::Method::GET);
+        assert_eq!(request.body().kind(), "query");
+        assert_eq!(keys, [
+            "pdbName", "ringmd", "chain", "networkPolicy", "seqSeparation",
+            "thresholds", "nohetero", "nowater", "noenergy", "msa",
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn result_engines() {
+        let job_id = JobId::from("5cefd030b265bd294b0f6b2c");
+        let raw = RetrieveRawResult { job_id: job_id.clone(), engine: ResultEngine::Cytoscape };
+
+        assert_eq!(RetrieveResult::new(job_id).endpoint(), "/results/5cefd030b265bd294b0f6b2c?engine=d3");
+        assert_eq!(raw.endpoint(), "/results/5cefd030b265bd294b0f6b2c?engine=cytoscape");
+    }
+
+    #[test]
+    fn job_progress() -> Result<()> {
+        let progress = |json: &str| -> Result<Option<f32>> {
+            let status: StatusResponse = serde_json::from_str(json)?;
+            Ok(status.progress)
+        };
+
+        assert_eq!(progress(r#"{ "_id": "x", "status": "partial" }"#)?, None);
+        assert_eq!(progress(r#"{ "_id": "x", "status": "partial", "progress": 0.25 }"#)?, Some(0.25));
+        assert_eq!(progress(r#"{ "_id": "x", "status": "partial", "progress": 1 }"#)?, Some(1.0));
+        assert_eq!(progress(r#"{ "_id": "x", "status": "partial", "progress": "0.5" }"#)?, Some(0.5));
+        assert_eq!(progress(r#"{ "_id": "x", "status": "partial", "progress": "75%" }"#)?, Some(0.75));
+        assert_eq!(progress(r#"{ "_id": "x", "status": "partial", "progress": 50 }"#)?, None);
+        assert_eq!(progress(r#"{ "_id": "x", "status": "partial", "progress": "BLAST iteration 2" }"#)?, None);
+        assert_eq!(progress(r#"{ "_id": "x", "status": "partial", "progress": [1, 2] }"#)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn proxy_configuration() -> Result<()> {
+        let proxy = reqwest::Proxy::all("http://proxy.example:3128")?.basic_auth("user", "secret");
+        let client = Client::builder()
+            .proxy(proxy)
+            .timeout(std::time::Duration::from_secs(5))
+            .user_agent("proxied/1.0")
+            .build()?;
+
+        assert_eq!(client.base_url(), "http://protein.bio.unipd.it/ringws");
+        assert!(Client::with_proxy(reqwest::Proxy::https("http://proxy.example:3128")?).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn mock_transport() -> Result<()> {
+        let job_id = JobId::from("5cefd030b265bd294b0f6b2c");
+        let mock = std::sync::Arc::new(MockTransport::new().respond_ok(
+            format!("status/{}", job_id),
+            r#"{ "_id": "5cefd030b265bd294b0f6b2c", "status": "complete" }"#,
+        ));
+        let client = Client::with_transport(mock.clone());
+        let status = client.send(Status { job_id: job_id.clone() })?;
+
+        assert_eq!(status.status, JobStatus::Complete);
+        assert!(client.job_exists(&job_id)?);
+        assert!(!client.job_exists(&JobId::from("0123456789abcdef01234567"))?);
+        assert_eq!(mock.requests().len(), 3);
+        assert!(mock.requests()[0].1.as_str().ends_with("/ringws/status/5cefd030b265bd294b0f6b2c"));
+
+        Ok(())
+    }
+
+    /// Replies to the `n`th request with the `n`th status, and to any
+    /// further requests with the last status, and counts the requests.
+    #[derive(Debug)]
+    struct StatusSequence {
+        /// The statuses to respond with, in order.
+        statuses: Vec<reqwest::StatusCode>,
+        /// The JSON body of every response.
+        body: &'static str,
+        /// The `Retry-After` header of every response, if any.
+        retry_after: Option<&'static str>,
+        /// The number of requests sent so far.
+        sent: std::sync::atomic::AtomicUsize,
+    }
+
+    impl StatusSequence {
+        /// Creates a shared transport which replies with `statuses` and `body`.
+        fn new(statuses: &[reqwest::StatusCode], body: &'static str) -> std::sync::Arc<Self> {
+            Self::with_retry_after(statuses, body, None)
+        }
+
+        /// Like `new()`, but also sends the given `Retry-After` header.
+        fn with_retry_after(
+            statuses: &[reqwest::StatusCode],
+            body: &'static str,
+            retry_after: Option<&'static str>,
+        ) -> std::sync::Arc<Self> {
+            std::sync::Arc::new(StatusSequence {
+                statuses: statuses.to_vec(),
+                body,
+                retry_after,
+                sent: Default::default(),
+            })
+        }
+
+        /// The number of requests sent so far.
+        fn sent(&self) -> usize {
+            self.sent.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    impl Transport for StatusSequence {
+        fn execute(&self, _: reqwest::Request) -> Result<reqwest::Response> {
+            let n = self.sent.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let status = self.statuses[n.min(self.statuses.len() - 1)];
+            let mut builder = http::Response::builder();
+            builder.status(status).header(reqwest::header::CONTENT_TYPE, "application/json");
+
+            if let Some(retry_after) = self.retry_after {
+                builder.header(reqwest::header::RETRY_AFTER, retry_after);
+            }
+
+            let response = builder.body(self.body).expect("invalid canned response");
+
+            Ok(reqwest::Response::from(response))
+        }
+    }
+
+    #[test]
+    fn transient_errors_retried() {
+        use std::time::Duration;
+        use reqwest::StatusCode;
+
+        let retry = RetryPolicy::new(3, Duration::from_millis(1));
+        let client_with = |transport| {
+            Client::builder().transport(transport).retry(retry).build().expect("can't build client")
+        };
+        let status = || Status { job_id: JobId::from("x") };
+
+        let recovering = StatusSequence::new(
+            &[StatusCode::SERVICE_UNAVAILABLE, StatusCode::OK],
+            r#"{ "_id": "x", "status": "complete" }"#,
+        );
+        let response = client_with(recovering.clone()).send(status()).expect("request not retried");
+        assert_eq!(response.status, JobStatus::Complete);
+        assert_eq!(recovering.sent(), 2);
+
+        let submission = StatusSequence::new(
+            &[StatusCode::SERVICE_UNAVAILABLE, StatusCode::OK],
+            r#"{ "jobid": "x", "status": "db" }"#,
+        );
+        assert!(client_with(submission.clone()).send(SubmitId::with_pdb_id("2ace")).is_err());
+        assert_eq!(submission.sent(), 1);
+
+        let down = StatusSequence::new(&[StatusCode::SERVICE_UNAVAILABLE], "{}");
+        let error = client_with(down.clone()).send(status()).expect_err("503 was accepted");
+        assert!(matches!(error, Error::Http { status: StatusCode::SERVICE_UNAVAILABLE, .. }));
+        assert_eq!(down.sent(), 4);
+    }
+
+    #[test]
+    fn retry_after_capped() {
+        use std::time::{ Duration, Instant };
+        use reqwest::StatusCode;
+
+        let transport = StatusSequence::with_retry_after(
+            &[StatusCode::TOO_MANY_REQUESTS, StatusCode::OK],
+            r#"{ "_id": "x", "status": "complete" }"#,
+            Some("86400"),
+        );
+        let client = Client::builder()
+            .transport(transport.clone())
+            .retry(RetryPolicy::new(1, Duration::from_millis(1)).max_delay(Duration::from_millis(10)))
+            .build()
+            .expect("can't build client");
+        let start = Instant::now();
+
+        assert!(client.send(Status { job_id: JobId::from("x") }).is_ok());
+        assert_eq!(transport.sent(), 2);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    /// Replies `200 OK` with a status and an optional `ETag`, or `304 Not
+    /// Modified` if the request carries that `ETag` in `If-None-Match`.
+    /// Records the `If-None-Match` header of every request.
+    #[derive(Debug, Default)]
+    struct Revalidating {
+        /// The entity tag of the (only) representation, if any.
+        etag: Option<&'static str>,
+        /// The `If-None-Match` header of each request, if any.
+        if_none_match: std::sync::Mutex<Vec<Option<String>>>,
+    }
+
+    impl Transport for Revalidating {
+        fn execute(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+            use reqwest::header::{ ETAG, IF_NONE_MATCH, CONTENT_TYPE };
+
+            let if_none_match = request.headers()
+                .get(IF_NONE_MATCH)
+                .map(|value| value.to_str().expect("non-ASCII If-None-Match").to_owned());
+            let fresh = self.etag.is_some() && if_none_match.as_deref() == self.etag;
+            self.if_none_match.lock().expect("poisoned lock").push(if_none_match);
+
+            let mut builder = http::Response::builder();
+            builder.header(CONTENT_TYPE, "application/json");
+
+            if let Some(etag) = self.etag {
+                builder.header(ETAG, etag);
+            }
+
+            let response = if fresh {
+                builder.status(reqwest::StatusCode::NOT_MODIFIED).body("")
+            } else {
+                builder.status(reqwest::StatusCode::OK).body(r#"{ "_id": "x", "status": "complete" }"#)
+            };
+
+            Ok(reqwest::Response::from(response.expect("invalid canned response")))
+        }
+    }
+
+    #[test]
+    fn conditional_get() -> Result<()> {
+        let transport = std::sync::Arc::new(Revalidating { etag: Some("\"v1\""), ..Default::default() });
+        let client = Client::builder()
+            .transport(transport.clone())
+            .response_cache(true)
+            .build()?;
+        let status = || Status { job_id: JobId::from("x") };
+
+        let (first, first_meta) = client.send_with_meta(status())?;
+        let (second, second_meta) = client.send_with_meta(status())?;
+
+        assert!(!first_meta.is_not_modified());
+        assert!(second_meta.is_not_modified());
+        assert_eq!(first, second);
+        assert_eq!(second.status, JobStatus::Complete);
+        assert_eq!(
+            *transport.if_none_match.lock().expect("poisoned lock"),
+            [None, Some(String::from("\"v1\""))],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn no_validators_not_cached() -> Result<()> {
+        let transport = std::sync::Arc::new(Revalidating::default());
+        let client = Client::builder()
+            .transport(transport.clone())
+            .response_cache(true)
+            .build()?;
+
+        for _ in 0..2 {
+            let (status, meta) = client.send_with_meta(Status { job_id: JobId::from("x") })?;
+            assert_eq!(status.status, JobStatus::Complete);
+            assert!(!meta.is_not_modified());
+        }
+
+        assert_eq!(*transport.if_none_match.lock().expect("poisoned lock"), [None, None]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn permanent_errors_not_retried() {
+        use std::time::Duration;
+        use std::sync::atomic::{ AtomicUsize, Ordering };
+
+        /// Fails every request with an invalid header error, and counts them.
+        #[derive(Debug, Default)]
+        struct InvalidHeader(AtomicUsize);
+
+        impl Transport for InvalidHeader {
+            fn execute(&self, _: reqwest::Request) -> Result<reqwest::Response> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                let error = reqwest::Client::new()
+                    .get("http://ring.example.org")
+                    .header("invalid header", "x")
+                    .build()
+                    .expect_err("header name with a space was accepted");
+                Err(Error::Reqwest(error))
+            }
+        }
+
+        let transport = std::sync::Arc::new(InvalidHeader::default());
+        let client = Client::builder()
+            .transport(transport.clone())
+            .retry(RetryPolicy::new(3, Duration::from_millis(1)))
+            .build()
+            .expect("can't build client");
+
+        assert!(client.send(Status { job_id: JobId::from("x") }).is_err());
+        assert_eq!(transport.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn query_submission_not_retried() {
+        use std::time::Duration;
+
+        let mock = std::sync::Arc::new(MockTransport::new().respond(
+            reqwest::Method::GET,
+            "submit",
+            reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            "{}",
+        ));
+        let client = Client::builder()
+            .transport(mock.clone())
+            .retry(RetryPolicy::new(3, Duration::from_millis(1)))
+            .response_cache(true)
+            .build()
+            .expect("can't build client");
+
+        assert!(!SubmitIdQuery::is_idempotent());
+        assert!(Status::is_idempotent());
+        assert!(client.send(SubmitIdQuery::with_pdb_id("2ace")).is_err());
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[test]
+    fn http_errors() {
+        let error = Client::with_transport(MockTransport::new())
+            .send(Status { job_id: JobId::from("x") })
+            .expect_err("404 was accepted");
+
+        match error {
+            Error::Http { status, ref body } => {
+                assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+                assert_eq!(body, "no canned response for GET ringws/status/x");
+            }
+            ref other => panic!("expected an HTTP error, got {:?}", other),
+        }
+
+        assert_eq!(error.to_string(), "HTTP error: 404 Not Found: no canned response for GET ringws/status/x");
+
+        let empty = Error::Http { status: reqwest::StatusCode::BAD_GATEWAY, body: String::from(" \n") };
+        assert_eq!(empty.to_string(), "HTTP error: 502 Bad Gateway");
+    }
+
+    #[test]
+    fn ring_version() -> Result<()> {
+        for &key in &["version", "ringVersion"] {
+            let json = format!(r#"{{ "_id": "x", "status": "complete", "{}": "2.0.1" }}"#, key);
+            let status: StatusResponse = serde_json::from_str(&json)?;
+            assert_eq!(status.ring_version.as_deref(), Some("2.0.1"));
+
+            let mut result = serde_json::to_value(RetrieveResultResponse::empty(JobId::from("x")))?;
+            result[key] = "2.0.1".into();
+            let parsed: RetrieveResultResponse = serde_json::from_value(result)?;
+            assert_eq!(parsed.ring_version.as_deref(), Some("2.0.1"));
+        }
+
+        let status: StatusResponse = serde_json::from_str(r#"{ "_id": "x", "status": "complete" }"#)?;
+        let result = serde_json::to_value(RetrieveResultResponse::empty(JobId::from("x")))?;
+        assert_eq!(status.ring_version, None);
+        assert_eq!(serde_json::from_value::<RetrieveResultResponse>(result)?.ring_version, None);
+
+        let versioned = MockTransport::new().respond_ok("version", r#"{ "ringVersion": "2.0.1" }"#);
+        assert_eq!(Client::with_transport(versioned).server_version()?.as_deref(), Some("2.0.1"));
+        assert_eq!(Client::with_transport(MockTransport::new()).server_version()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn submit_response_settings() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let bare: SubmitResponse = serde_json::from_str(r#"{ "jobid": "x", "status": "db" }"#)?;
+        let echoed: SubmitResponse = serde_json::from_str(r#"{
+            "jobid": "x", "status": "db", "chain": "B", "thresholds": "relaxed", "nowater": "false"
+        }"#)?;
+
+        assert_eq!(bare.settings, Settings::default());
+        assert_eq!(echoed.settings.chain, Chain::Id("B".parse()?));
+        assert_eq!(echoed.settings.thresholds, Thresholds::relaxed());
+        assert!(!echoed.settings.skip_water);
+
+        Ok(())
+    }
+
+    #[test]
+    fn submit_many() {
+        let mock = MockTransport::new().respond(
+            reqwest::Method::POST,
+            "submit",
+            reqwest::StatusCode::OK,
+            r#"{ "jobid": "5cefd030b265bd294b0f6b2c", "status": "db" }"#,
+        );
+        let client = Client::with_transport(mock);
+        let requests = vec![SubmitId::with_pdb_id("2ace"); 5];
+        let responses = client.submit_many(requests, 2);
+
+        assert_eq!(responses.len(), 5);
+        assert!(responses.iter().all(|response| matches!(
+            *response,
+            Ok(SubmitResponse { status: JobStatus::InProgress, .. })
+        )));
+        assert!(client.submit_many(Vec::<SubmitId>::new(), 0).is_empty());
+    }
+
+    #[test]
+    fn job_id_validation() {
+        let job_id = JobId::from("5cefd030b265bd294b0f6b2c");
+
+        assert!(job_id.is_valid());
+        assert!(JobId::new("").is_err());
+        assert!(JobId::from("").is_empty());
+        assert_eq!(JobId::new("5cefd030b265bd294b0f6b2c").ok(), Some(job_id));
+        assert!(JobId::new("5cefd030b265bd294b0f6b2").is_err());
+        assert!(JobId::new("5cefd030b265bd294b0f6b2g").is_err());
+        assert!(!JobId::from("x").is_valid());
+    }
+
+    #[test]
+    fn job_tags() {
+        let client = Client::new();
+        let clone = client.clone();
+        let job_id = JobId::from("5cefd030b265bd294b0f6b2c");
+
+        assert_eq!(client.set_tag(job_id.clone(), "apo"), None);
+        assert_eq!(clone.tag_of(&job_id), Some(String::from("apo")));
+        assert_eq!(clone.jobs_tagged("apo"), vec![job_id.clone()]);
+        assert_eq!(client.remove_tag(&job_id), Some(String::from("apo")));
+        assert_eq!(clone.tag_of(&job_id), None);
+    }
+
+    /// Convenience constructor for node IDs in hand-built results.
+    fn node_id(chain: &str, position: isize, residue: Residue) -> NodeId {
+        let chain_id = chain.parse().expect("invalid chain ID");
+        NodeId { chain_id, position, insertion_code: '_', residue }
+    }
+
+    /// Builds a result with the given nodes and an edge of the given
+    /// distance between each specified pair of nodes.
+    fn network(nodes: &[NodeId], edges: &[(usize, usize, f64)]) -> RetrieveResultResponse {
+        let mut result = RetrieveResultResponse::empty(JobId::from("x"));
+        let interaction: Interaction = "VDW:SC_SC".parse().expect("valid interaction");
+
+        result.nodes = nodes.iter().cloned().map(Node::new).collect();
+        result.edges = edges
+            .iter()
+            .map(|&(i, j, distance)| Edge {
+                distance,
+                ..Edge::new(nodes[i], nodes[j], interaction)
+            })
+            .collect();
+
+        result
+    }
+
+    /// A mock which reports the given status for job `x`, and serves its result.
+    fn job_mock(status: &str) -> std::sync::Arc<MockTransport> {
+        let result = network(&[node_id("A", 1, Residue::Alanine)], &[]);
+        let status_json = format!(r#"{{ "_id": "x", "status": "{}", "error": "bad chain" }}"#, status);
+        let result_json = serde_json::to_string(&result).expect("can't serialize result");
+
+        std::sync::Arc::new(
+            MockTransport::new()
+                .respond_ok("status/x", status_json)
+                .respond_ok("results/x", result_json)
+        )
+    }
+
+    #[test]
+    fn wait_for_result() -> Result<()> {
+        use std::time::Duration;
+
+        let job_id = JobId::from("x");
+        let poll = Duration::from_millis(1);
+
+        let complete = job_mock("complete");
+        let result = Client::with_transport(complete.clone()).wait_for_result(&job_id, poll, None)?;
+        assert_eq!(result.nodes.len(), 1);
+        assert_eq!(complete.requests().len(), 2);
+
+        let failed = Client::with_transport(job_mock("error")).wait_for_result(&job_id, poll, None);
+        assert!(matches!(
+            failed,
+            Err(Error::JobFailed { ref reason, .. }) if reason.as_deref() == Some("bad chain")
+        ));
+
+        // the poll interval is longer than the timeout, so this polls
+        // once immediately, and once more when the timeout expires
+        let in_progress = job_mock("db");
+        let timed_out = Client::with_transport(in_progress.clone()).wait_for_result(
+            &job_id,
+            Duration::from_secs(60),
+            Some(Duration::from_millis(20)),
+        );
+        assert!(matches!(timed_out, Err(Error::JobTimeout { .. })));
+        assert_eq!(in_progress.requests().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn wait_for_partial_result() -> Result<()> {
+        use std::time::Duration;
+
+        let job_id = JobId::from("x");
+        let partial = job_mock("partial");
+        let client = Client::with_transport(partial.clone());
+        let result = client.wait_for_partial_result(&job_id, Duration::from_millis(1), None)?;
+
+        assert_eq!(result.nodes.len(), 1);
+        assert_eq!(partial.requests().len(), 2);
+
+        let still_waiting = client.wait_for_result(
+            &job_id,
+            Duration::from_millis(1),
+            Some(Duration::from_millis(20)),
+        );
+        assert!(matches!(still_waiting, Err(Error::JobTimeout { .. })));
+        assert!(partial.requests().len() >= 4);
+        assert!(partial.requests()[2..].iter().all(|(_, url)| url.path().ends_with("/status/x")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn submit_and_wait() -> Result<()> {
+        use std::time::Duration;
+        use reqwest::{ Method, StatusCode };
+
+        let result = network(&[node_id("A", 1, Residue::Alanine)], &[]);
+        let mock = std::sync::Arc::new(
+            MockTransport::new()
+                .respond(Method::POST, "submit", StatusCode::OK, r#"{ "jobid": "x", "status": "db" }"#)
+                .respond_ok("status/x", r#"{ "_id": "x", "status": "complete" }"#)
+                .respond_ok("results/x", serde_json::to_string(&result)?)
+        );
+        let client = Client::with_transport(mock.clone());
+        let retrieved = client.submit_and_wait(SubmitId::with_pdb_id("2ace"), Duration::from_millis(1))?;
+        let paths: Vec<_> = mock.requests().iter().map(|(_, url)| url.path().to_owned()).collect();
+
+        assert_eq!(retrieved, result);
+        assert_eq!(paths, ["/ringws/submit", "/ringws/status/x", "/ringws/results/x"]);
+        assert_eq!(mock.requests()[0].0, Method::POST);
+
+        Ok(())
+    }
+
+    #[test]
+    fn isolated_nodes() {
+        let ids: Vec<_> = (1..=4).map(|i| node_id("A", i, Residue::Glycine)).collect();
+        let mut result = network(&ids, &[(0, 1, 3.5), (1, 2, 4.0)]);
+
+        for (node, &degree) in result.nodes.iter_mut().zip(&[1, 2, 1, 0]) {
+            node.degree = degree;
+        }
+
+        let isolated: Vec<_> = result.isolated_nodes().iter().map(|node| node.node_id).collect();
+        assert_eq!(isolated, [ids[3]]);
+        assert!((result.interacting_residue_fraction() - 0.75).abs() < 1e-9);
+
+        let empty = network(&[], &[]);
+        assert!(empty.isolated_nodes().is_empty());
+        assert!(empty.interacting_residue_fraction().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn cumulative_spellings() -> Result<()> {
+        let node = serde_json::to_value(Node::new(node_id("A", 1, Residue::Alanine)))?;
+
+        for key in &["MIcomulative", "MIcumulative"] {
+            let mut misspelled = node.clone();
+            misspelled[*key] = 0.25.into();
+            let parsed: Node = serde_json::from_value(misspelled)?;
+            assert_eq!(parsed.cumul_mutual_entropy, Some(0.25));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_fields_are_kept() -> Result<()> {
+        let node = Node::new(node_id("A", 1, Residue::Alanine));
+        let mut json = serde_json::to_value(&node)?;
+
+        assert!(json.get("extra").is_none());
+        assert!(json.get("pdbFileName").is_none());
+
+        json["NewEnergy"] = serde_json::Value::from(-1.5);
+
+        let decoded: Node = serde_json::from_value(json)?;
+
+        assert_eq!(decoded.extra.get("NewEnergy"), Some(&serde_json::Value::from(-1.5)));
+        assert_eq!(Node { extra: Default::default(), ..decoded }, node);
+
+        Ok(())
+    }
+
+    #[test]
+    fn neighbors() {
+        let ids: Vec<_> = (1..=4).map(|i| node_id("A", i, Residue::Leucine)).collect();
+        let result = network(&ids, &[(0, 1, 3.0), (2, 0, 3.0), (1, 0, 4.0), (2, 3, 3.0)]);
+        let edge = &result.edges[1];
+
+        assert!(edge.involves(&ids[0]) && edge.involves(&ids[2]) && !edge.involves(&ids[1]));
+        assert_eq!(edge.other(&ids[0]), Some(&ids[2]));
+        assert_eq!(edge.other(&ids[2]), Some(&ids[0]));
+        assert_eq!(edge.other(&ids[3]), None);
+        assert_eq!(
+            result.neighbors(&ids[0]).collect::<Vec<_>>(),
+            vec![&ids[1], &ids[2], &ids[1]]
+        );
+        assert_eq!(result.distinct_neighbors(&ids[0]), vec![&ids[1], &ids[2]]);
+    }
+
+    #[test]
+    fn nodes_by_chain() -> Result<()> {
+        let inserted = NodeId { insertion_code: 'A', ..node_id("B", -1, Residue::Serine) };
+        let ids = [
+            node_id("B", 2, Residue::Glycine),
+            inserted,
+            node_id("A", 5, Residue::Alanine),
+            node_id("B", -1, Residue::Proline),
+        ];
+        let result = network(&ids, &[]);
+        let chain_b = "B".parse()?;
+        let sorted: Vec<_> = result
+            .nodes_in_chain_sorted(chain_b)
+            .into_iter()
+            .map(|node| node.node_id)
+            .collect();
+
+        assert_eq!(result.chains().into_iter().collect::<Vec<_>>(), ["A".parse()?, chain_b]);
+        assert_eq!(result.nodes_in_chain(chain_b).count(), 3);
+        assert_eq!(sorted, [ids[3], inserted, ids[0]]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn residues_within() {
+        let ids: Vec<_> = (1..=3).map(|i| node_id("A", i, Residue::Valine)).collect();
+        let mut result = network(&ids, &[]);
+
+        result.nodes[1].x = 3.0;
+        result.nodes[1].y = 4.0;
+        result.nodes[2].z = 10.0;
+
+        assert_eq!(result.nodes[1].coords(), [3.0, 4.0, 0.0]);
+        assert!((result.nodes[0].distance_to(&result.nodes[1]) - 5.0).abs() < 1e-12);
+        assert_eq!(
+            result.residues_within(&ids[0], 6.0).iter().map(|node| node.node_id).collect::<Vec<_>>(),
+            [ids[1]]
+        );
+        assert!(result.residues_within(&node_id("B", 1, Residue::Valine), 100.0).is_empty());
+    }
+
+    #[test]
+    fn measurement_basis() -> Result<()> {
+        let mut edge = Edge::new(
+            node_id("A", 1, Residue::Lysine),
+            node_id("A", 2, Residue::Tryptophan),
+            "PICATION:SC_SC".parse()?,
+        );
+
+        edge.atom_1 = "NZ".parse()?;
+        edge.atom_2 = "1.0,2.0,3.0".parse()?;
+        assert_eq!(edge.measured_between(), MeasurementBasis::AtomAndCenter);
+
+        edge.atom_1 = "CA".parse()?;
+        edge.atom_2 = "CA".parse()?;
+        assert_eq!(edge.measured_between(), MeasurementBasis::AlphaCarbons);
+
+        edge.atom_1 = "0.0,0.0,0.0".parse()?;
+        edge.atom_2 = "1.0,2.0,3.0".parse()?;
+        assert_eq!(edge.measured_between(), MeasurementBasis::Centers);
+
+        Ok(())
+    }
+
+    #[test]
+    fn chain_subgraph() -> Result<()> {
+        let ids = [
+            node_id("A", 1, Residue::Alanine),
+            node_id("A", 2, Residue::Serine),
+            node_id("B", 1, Residue::Lysine),
+        ];
+        let result = network(&ids, &[(0, 1, 3.0), (1, 2, 4.0)]);
+        let chain_a = "A".parse()?;
+        let intra = result.subgraph_for_chain(chain_a, false);
+        let inter = result.subgraph_for_chain(chain_a, true);
+
+        assert_eq!(intra.nodes.len(), 2);
+        assert_eq!(intra.edges.len(), 1);
+        assert_eq!(intra.nodes[1].degree, 1);
+        assert_eq!(inter.edges.len(), 2);
+        assert_eq!(inter.nodes[1].degree, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn interaction_predicates() -> Result<()> {
+        let interaction: Interaction = "HBOND:MC_SC".parse()?;
+
+        assert!(interaction.is_hydrogen_bond());
+        assert!(!interaction.is_ionic());
+        assert!(interaction.involves_main_chain() && interaction.involves_side_chain());
+        assert!(!interaction.involves_ligand());
+        assert!("PIPISTACK:SC_SC".parse::<Interaction>()?.is_pi_pi());
+
+        Ok(())
+    }
+
+    #[test]
+    fn node_stats() {
+        let ids: Vec<_> = (1..=4).map(|i| node_id("A", i, Residue::Alanine)).collect();
+        let mut result = network(&ids, &[]);
+
+        for (node, &value) in result.nodes.iter_mut().zip(&[4.0, 1.0, f64::NAN, 2.0]) {
+            node.accessibility = value;
+        }
+
+        let stats = result.accessibility_stats().expect("finite values");
+
+        assert_eq!(stats, Stats { count: 3, min: 1.0, max: 4.0, mean: 7.0 / 3.0, median: 2.0 });
+        assert_eq!(Stats::from_values(vec![1.0, 2.0]).map(|s| s.median), Some(1.5));
+        assert_eq!(RetrieveResultResponse::empty(JobId::from("x")).bfactor_stats(), None);
+    }
+
+    #[test]
+    fn merged_edges() -> Result<()> {
+        let ids: Vec<_> = (1..=3).map(|i| node_id("A", i, Residue::Tyrosine)).collect();
+        let mut result = network(&ids, &[(0, 1, 3.0), (1, 0, 4.0), (1, 2, 5.0), (0, 1, 3.5)]);
+
+        result.edges[1].interaction = "PIPISTACK:SC_SC".parse()?;
+        for edge in &mut result.edges {
+            edge.energy = 1.5;
+        }
+
+        let merged = result.merged_edges();
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!((merged[0].node_id_1, merged[0].node_id_2), (ids[0], ids[1]));
+        assert_eq!(merged[0].edge_count, 3);
+        assert_eq!(merged[0].interactions.len(), 2);
+        assert!(merged[0].has_type(InteractionMainType::PiPiStack));
+        assert_eq!(merged[0].min_distance, 3.0);
+        assert_eq!(merged[0].total_energy, 4.5);
+        assert_eq!(merged[1].edge_count, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn result_iterators() {
+        let ids: Vec<_> = (1..=3).map(|i| node_id("A", i, Residue::Valine)).collect();
+        let result = network(&ids, &[(0, 1, 3.0), (1, 2, 4.0)]);
+
+        assert!(result.nodes().map(|node| node.node_id).eq(ids.iter().copied()));
+        assert!(result.edges().map(|edge| edge.distance).eq(vec![3.0, 4.0]));
+        assert_eq!(result.clone().into_nodes().count(), 3);
+        assert_eq!(result.into_edges().last().map(|edge| edge.node_id_2), Some(ids[2]));
+    }
+
+    #[test]
+    fn energy_ranking() {
+        let ids: Vec<_> = (1..=5).map(|i| node_id("A", i, Residue::Lysine)).collect();
+        let mut result = network(&ids, &[(0, 1, 1.0), (1, 2, 2.0), (2, 3, 3.0), (3, 4, 4.0)]);
+
+        for (edge, &energy) in result.edges.iter_mut().zip(&[f64::NAN, 5.0, 20.0, 5.0]) {
+            edge.energy = energy;
+        }
+
+        let distances = |edges: Vec<&Edge>| -> Vec<f64> {
+            edges.iter().map(|edge| edge.distance).collect()
+        };
+
+        assert_eq!(distances(result.edges_by_energy()), [3.0, 2.0, 4.0, 1.0]);
+        assert_eq!(distances(result.strongest_edges(2)), [3.0, 2.0]);
+        assert_eq!(result.strongest_edges(10).len(), 4);
+    }
+
+    #[test]
+    fn edge_query() -> Result<()> {
+        let a: Vec<_> = (1..=2).map(|i| node_id("A", i, Residue::Serine)).collect();
+        let b = node_id("B", 1, Residue::Serine);
+        let mut result = network(&[a[0], a[1], b], &[(0, 1, 3.0), (2, 0, 3.5), (1, 2, 4.5), (0, 2, 3.2)]);
+
+        for &i in &[0, 1, 2] {
+            result.edges[i].interaction = "HBOND:MC_SC".parse()?;
+        }
+
+        let bonds = result
+            .query()
+            .main_type(InteractionMainType::HydrogenBond)
+            .between_chains("A".parse()?, "B".parse()?);
+
+        assert_eq!(result.query().count(), 4);
+        assert_eq!(bonds.collect(), [&result.edges[1], &result.edges[2]]);
+        assert_eq!(bonds.max_distance(4.0).collect(), [&result.edges[1]]);
+        assert_eq!(bonds.involving(a[0]).count(), 1);
+        assert_eq!(result.query().between_chains("A".parse()?, "A".parse()?).count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn result_diff() -> Result<()> {
+        let ids: Vec<_> = (1..=4).map(|i| node_id("A", i, Residue::Serine)).collect();
+        let mut strict = network(&ids[..3], &[(0, 1, 3.0), (1, 2, 3.0)]);
+        let mut relaxed = network(&ids, &[(2, 1, 4.0), (2, 3, 4.0), (1, 0, 4.0)]);
+
+        strict.edges[0].interaction = "HBOND:MC_SC".parse()?;
+        relaxed.edges[2].interaction = "HBOND:SC_MC".parse()?;
+
+        let diff = strict.diff(&relaxed);
+
+        assert!(strict.diff(&strict).is_empty());
+        assert_eq!(diff.added_nodes.iter().collect::<Vec<_>>(), [&ids[3]]);
+        assert!(diff.removed_nodes.is_empty());
+        assert_eq!(diff.added_edges.iter().collect::<Vec<_>>(), [&EdgeKey::from(&relaxed.edges[1])]);
+        assert!(diff.removed_edges.is_empty());
+        assert_eq!(relaxed.diff(&strict).removed_nodes, diff.added_nodes);
+
+        let keys: std::collections::BTreeSet<_> = relaxed.edges.iter().map(Edge::key).collect();
+        let forward = Edge::new(ids[0], ids[1], "HBOND:MC_SC".parse()?);
+        assert_eq!(keys.len(), 3);
+        assert!(keys.contains(&forward.key()));
+        assert_eq!(relaxed.nodes[3].key(), ids[3]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_angle() {
+        assert_eq!(angle_from_raw(INVALID_ANGLE), None);
+        assert_eq!(angle_from_raw(f64::from(-999.9_f32)), None);
+        assert_eq!(angle_from_raw(-999.0), Some(-999.0));
+        assert_eq!(angle_from_raw(12.5), Some(12.5));
+        assert_eq!(angle_to_raw(None), INVALID_ANGLE);
+        assert_eq!(angle_to_raw(Some(12.5)), 12.5);
+    }
+
+    #[test]
+    fn atom_parsing() -> Result<()> {
+        let name = |s: &str| Atom::Name(s.into());
+
+        assert_eq!("CA".parse::<Atom>()?, name("CA"));
+        assert_eq!("1.0,2.0,x".parse::<Atom>()?, name("1.0,2.0,x"));
+        assert_eq!("1,2,3,4".parse::<Atom>()?, name("1,2,3,4"));
+        assert_eq!("A,B,C".parse::<Atom>()?, name("A,B,C"));
+        assert_eq!("1, -2.5,3e1".parse::<Atom>()?, Atom::Coords { x: 1.0, y: -2.5, z: 30.0 });
+
+        Ok(())
+    }
+
+    #[test]
+    fn interaction_counts() {
+        let ids: Vec<_> = (1..=3).map(|i| node_id("A", i, Residue::Serine)).collect();
+        let mut result = network(&ids, &[(0, 1, 3.0), (1, 2, 3.0)]);
+        let hbond = "HBOND:MC_SC".parse().expect("valid interaction");
+
+        result.edges.push(Edge::new(ids[0], ids[2], hbond));
+
+        let counts = result.interaction_counts();
+        let subtypes = result.subtype_counts();
+
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&InteractionMainType::VanDerWaals], 2);
+        assert_eq!(counts[&InteractionMainType::HydrogenBond], 1);
+        assert_eq!(subtypes[&(InteractionSubType::SideChain, InteractionSubType::SideChain)], 2);
+        assert_eq!(subtypes[&(InteractionSubType::MainChain, InteractionSubType::SideChain)], 1);
+    }
+
+    #[test]
+    fn degree_consistency() {
+        let ids: Vec<_> = (-1..=2).map(|i| node_id("A", i, Residue::Valine)).collect();
+        let mut result = network(&ids, &[(0, 1, 3.0), (1, 2, 3.0), (0, 2, 3.0)]);
+        let degrees = result.recompute_degrees();
+
+        assert_eq!(degrees[&ids[0]], 2);
+        assert_eq!(degrees[&ids[1]], 2);
+        assert_eq!(degrees[&ids[3]], 0);
+        assert!(result.verify_degrees().is_err());
+
+        for node in &mut result.nodes {
+            node.degree = degrees[&node.node_id];
+        }
+
+        assert!(result.verify_degrees().is_ok());
+    }
+
+    #[test]
+    fn betweenness_centrality() {
+        let ids: Vec<_> = (1..=4).map(|i| node_id("A", i, Residue::Glycine)).collect();
+        let close = |x: f64, y: f64| (x - y).abs() < 1e-12;
+
+        // path: 0 - 1 - 2
+        let path = network(&ids[..3], &[(0, 1, 1.0), (1, 2, 1.0)]);
+        let path_bc = path.betweenness_centrality();
+        assert!(close(path_bc[&ids[0]], 0.0));
+        assert!(close(path_bc[&ids[1]], 1.0));
+        assert!(close(path_bc[&ids[2]], 0.0));
+
+        // square: 0 - 1 - 2 - 3 - 0, the 2-3 and 3-0 edges being long
+        let square = network(&ids, &[(0, 1, 1.0), (1, 2, 1.0), (2, 3, 5.0), (3, 0, 5.0)]);
+        let square_bc = square.betweenness_centrality();
+        for id in &ids {
+            assert!(close(square_bc[id], 1.0 / 6.0));
+        }
+
+        let weighted_bc = square.betweenness_centrality_weighted(EdgeWeight::Distance);
+        assert!(close(weighted_bc[&ids[0]], 1.0 / 6.0));
+        assert!(close(weighted_bc[&ids[1]], 1.0 / 3.0));
+        assert!(close(weighted_bc[&ids[2]], 1.0 / 6.0));
+        assert!(close(weighted_bc[&ids[3]], 0.0));
+    }
+
+    #[test]
+    fn shortest_path() {
+        let ids: Vec<_> = (-2..=3).map(|i| node_id("A", i, Residue::Serine)).collect();
+        let result = network(&ids, &[
+            (0, 1, 1.0),
+            (1, 2, 1.0),
+            (2, 3, 1.0),
+            (0, 4, 10.0),
+            (4, 3, 10.0),
+        ]);
+        let path = |i: usize, j: usize, weight| {
+            result.shortest_path(&ids[i], &ids[j], weight)
+        };
+
+        assert_eq!(path(0, 3, None), Some(vec![ids[0], ids[4], ids[3]]));
+        assert_eq!(
+            path(0, 3, Some(EdgeWeight::Distance)),
+            Some(vec![ids[0], ids[1], ids[2], ids[3]])
+        );
+        assert_eq!(path(2, 2, None), Some(vec![ids[2]]));
+        assert_eq!(path(0, 5, None), None);
+        assert_eq!(path(5, 0, Some(EdgeWeight::Distance)), None);
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn petgraph_conversion() {
+        let ids: Vec<_> = (1..=3).map(|i| node_id("A", i, Residue::Glycine)).collect();
+        let mut result = network(&ids, &[(0, 1, 3.5), (1, 2, 4.0)]);
+        let stray = node_id("B", 7, Residue::Glycine);
+        let edge = Edge::new(ids[0], stray, result.edges[0].interaction);
+
+        result.edges.push(edge);
+
+        let graph = result.to_petgraph();
+
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 2);
+        assert!(graph.raw_edges().iter().all(|e| e.weight.node_id_2 != stray));
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn retrieve_graph() -> Result<()> {
+        let ids: Vec<_> = (1..=3).map(|i| node_id("A", i, Residue::Glycine)).collect();
+        let result = network(&ids, &[(0, 1, 3.5), (1, 2, 4.0)]);
+        let job_id = JobId::from("5cefd030b265bd294b0f6b2c");
+        let mock = MockTransport::new().respond_ok(
+            format!("results/{}", job_id),
+            serde_json::to_string(&result)?,
+        );
+        let graph = Client::with_transport(mock).retrieve_graph(&job_id)?;
+
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 2);
+        assert!(Client::with_transport(MockTransport::new()).retrieve_graph(&job_id).is_err());
+
+        let mut dangling = result.clone();
+        dangling.nodes.pop();
+        let dangling_mock = MockTransport::new().respond_ok(
+            format!("results/{}", job_id),
+            serde_json::to_string(&dangling)?,
+        );
+        let error = Client::with_transport(dangling_mock)
+            .retrieve_graph(&job_id)
+            .expect_err("dangling edge was accepted");
+
+        assert!(matches!(error, Error::Validation(_)));
+        assert_eq!(dangling.to_petgraph().edge_count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn node_lookup() {
+        let ids: Vec<_> = (1..=3).map(|i| node_id("A", i, Residue::Lysine)).collect();
+        let result = network(&ids, &[(0, 1, 3.0)]);
+        let index = result.node_index();
+        let missing = node_id("B", 1, Residue::Lysine);
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index[&ids[1]].position, 2);
+        assert_eq!(result.node(&ids[2]).map(|node| node.node_id), Some(ids[2]));
+        assert!(result.node(&missing).is_none());
+    }
+
+    #[test]
+    fn result_files() -> Result<()> {
+        let ids = [node_id("A", 1, Residue::Alanine), node_id("A", 2, Residue::Serine)];
+        let result = network(&ids, &[(0, 1, 3.5)]);
+        let path = std::env::temp_dir().join(format!("ring_api_result_{}.json", std::process::id()));
+
+        result.to_file(&path)?;
+        let loaded = RetrieveResultResponse::from_file(&path);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(loaded?, result);
+        assert_eq!(serde_json::to_string(&result)?.parse::<RetrieveResultResponse>()?, result);
+        assert!(matches!("{}".parse::<RetrieveResultResponse>(), Err(Error::Json(_))));
+        assert!(RetrieveResultResponse::from_file(&path).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn streaming_edges() -> Result<()> {
+        let ids: Vec<_> = (1..=3).map(|i| node_id("A", i, Residue::Glycine)).collect();
+        let result = network(&ids, &[(0, 1, 3.0), (1, 2, 4.0)]);
+        let json = serde_json::to_vec(&result)?;
+        let mut edges = Vec::new();
+        let streamed = RetrieveResultResponse::from_reader_streaming(
+            json.as_slice(),
+            |edge| edges.push(edge),
+        )?;
+
+        assert_eq!(edges, result.edges);
+        assert!(streamed.edges.is_empty());
+        assert_eq!(streamed.nodes, result.nodes);
+
+        Ok(())
+    }
+
+    #[test]
+    fn adjacency_matrix() {
+        let ids: Vec<_> = (1..=3).map(|i| node_id("A", i, Residue::Glycine)).collect();
+        let mut result = network(&ids, &[(0, 1, 3.0), (1, 0, 4.0), (2, 2, 1.0)]);
+
+        result.edges.push(Edge::new(ids[0], node_id("B", 1, Residue::Glycine), result.edges[0].interaction));
+
+        let (order, counts) = result.adjacency_matrix(AdjacencyWeight::EdgeCount);
+        let (_, unit) = result.sparse_adjacency_matrix(AdjacencyWeight::Unit);
+
+        assert_eq!(order, ids);
+        assert_eq!(counts, vec![vec![0.0, 2.0, 0.0], vec![2.0, 0.0, 0.0], vec![0.0, 0.0, 1.0]]);
+        assert_eq!(unit, vec![(0, 1, 1.0), (1, 0, 1.0), (2, 2, 1.0)]);
+    }
+
+    #[test]
+    fn dot_export() {
+        let ids = [node_id("A", 1, Residue::Alanine), node_id("A", 2, Residue::Serine)];
+        let dot = network(&ids, &[(0, 1, 3.0)]).to_dot();
+
+        assert!(dot.starts_with("graph ring {\n"));
+        assert!(dot.contains("\"A:1:_:ALA\" [label=\"ALA 1\"];"));
+        assert!(dot.contains("\"A:1:_:ALA\" -- \"A:2:_:SER\" [color=gray];"));
+        assert!(dot.ends_with("}\n"));
+
+        let odd = network(&[node_id(r#"\""#, 1, Residue::Alanine)], &[]).to_dot();
+        assert!(odd.contains(r#""\\\":1:_:ALA" [label="ALA 1"];"#));
+    }
+
+    #[test]
+    fn validate_edge_roles() {
+        let ala = node_id("A", 1, Residue::Alanine);
+        let ser = node_id("A", 2, Residue::Serine);
+        let mut result = RetrieveResultResponse::empty(JobId::from("x"));
+        let mut edge = Edge::new(ala, ser, "HBOND:MC_SC".parse().expect("valid interaction"));
+
+        result.nodes.push(Node::new(ala));
+        result.nodes.push(Node::new(ser));
+        result.edges.push(edge.clone());
+        assert!(result.validate().is_err());
+
+        edge.donor = Some(ser);
+        result.edges[0] = edge.clone();
+        assert!(result.validate().is_ok());
+
+        edge.interaction.main_type = InteractionMainType::VanDerWaals;
+        assert!(edge.validate_roles().is_err());
+    }
+
+    #[test]
+    fn interaction_round_trip() -> Result<()> {
+        for &main_type in InteractionMainType::all() {
+            for &subtype_1 in InteractionSubType::all() {
+                for &subtype_2 in InteractionSubType::all() {
+                    let interaction = Interaction { main_type, subtype_1, subtype_2 };
+                    let string = interaction.to_string();
+                    let json = serde_json::to_string(&interaction)?;
+
+                    assert_eq!(string.parse::<Interaction>()?, interaction);
+                    assert_eq!(json, format!("{:?}", string));
+                    assert_eq!(serde_json::from_str::<Interaction>(&json)?, interaction);
+                }
+            }
+        }
+
+        let node_ids = [
+            node_id("A", 42, Residue::Tryptophan),
+            node_id("B", 0, Residue::Unknown),
+            node_id("Z", -7, Residue::GlutamineOrGlutamicAcid),
+        ];
+
+        for node_id in &node_ids {
+            let json = serde_json::to_string(node_id)?;
+
+            assert_eq!(node_id.to_string().parse::<NodeId>()?, *node_id);
+            assert_eq!(serde_json::from_str::<NodeId>(&json)?, *node_id);
+        }
+
+        let atoms = [
+            Atom::Name(String::from("CA")),
+            Atom::Name(String::from("OD1")),
+            Atom::Coords { x: 1.0, y: -2.5, z: 1e-7 },
+            Atom::Coords { x: -0.0, y: 123.456, z: 9999.001 },
+        ];
+
+        for atom in &atoms {
+            let json = serde_json::to_string(atom)?;
+
+            assert_eq!(&atom.to_string().parse::<Atom>()?, atom);
+            assert_eq!(&serde_json::from_str::<Atom>(&json)?, atom);
+        }
 
         Ok(())
     }
@@ -146,7 +1817,7 @@ mod tests {
     fn retrieve_result() -> Result<()> {
         let client = Client::new();
         let job_id = JobId::from("5cefd030b265bd294b0f6b2c");
-        let request = RetrieveResult { job_id };
+        let request = RetrieveResult::new(job_id);
         let response = client.send(&request)?;
 
         println!("{:#?}", response.nodes[0]);
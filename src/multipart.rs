@@ -1,6 +1,9 @@
 //! Serialize a value into a request multipart `Form`.
 
 use std::borrow::Cow;
+use std::path::PathBuf;
+use std::fmt::{ Formatter, Result as FmtResult };
+use std::result::Result as StdResult;
 use serde::ser::{
     Error as SerError,
     Serialize,
@@ -13,28 +16,43 @@ use serde::ser::{
     SerializeStruct,
     SerializeStructVariant,
 };
+use serde::de::{ Deserialize, Deserializer, Visitor, SeqAccess, Error as DeError };
 use reqwest::multipart::{ Form, Part };
 use crate::error::{ Error, Result };
 
 /// Describes a file in a multipart form.
-/// The first string is the contents of the file, the second is the
+/// The first field is the contents of the file, the second is the
 /// file name.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename = "$FormFile")]
-pub struct FormFile(String, String);
+pub struct FormFile(FileContents, String);
+
+/// The contents of a file in a multipart form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FileContents {
+    /// UTF-8 text.
+    Text(String),
+    /// Arbitrary binary data, sent verbatim.
+    Bytes(Vec<u8>),
+    /// A file on disk, which is only opened when the form is built, and
+    /// is then streamed rather than read into memory. Useful for large
+    /// structures. (Serializers other than the multipart one see the
+    /// path as a string, so it deserializes as `Text`.)
+    Path(PathBuf),
+}
 
 impl FormFile {
     /// The constructor. First parameter is the file contents, the second is
     /// the file name.
     pub fn with_contents_and_file_name<T, U>(contents: T, file_name: U) -> Self
-        where T: Into<String>,
+        where T: Into<FileContents>,
               U: Into<String>,
     {
         FormFile(contents.into(), file_name.into())
     }
 
     /// Returns the contents, discarding the file name.
-    pub fn into_contents(self) -> String {
+    pub fn into_contents(self) -> FileContents {
         self.0
     }
 
@@ -44,7 +62,7 @@ impl FormFile {
     }
 
     /// Returns a reference to the contents.
-    pub fn contents(&self) -> &str {
+    pub fn contents(&self) -> &FileContents {
         &self.0
     }
 
@@ -54,8 +72,8 @@ impl FormFile {
     }
 
     /// Replaces the contents of this file part with the given argument.
-    pub fn set_contents(&mut self, contents: String) {
-        self.0 = contents;
+    pub fn set_contents<T: Into<FileContents>>(&mut self, contents: T) {
+        self.0 = contents.into();
     }
 
     /// Replaces the file name of this file part with the given argument.
@@ -64,6 +82,112 @@ impl FormFile {
     }
 }
 
+impl FileContents {
+    /// Returns the contents as raw bytes, if they are in memory.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match *self {
+            FileContents::Text(ref text) => Some(text.as_bytes()),
+            FileContents::Bytes(ref bytes) => Some(bytes),
+            FileContents::Path(_) => None,
+        }
+    }
+
+    /// Returns the contents as a string, if they are text.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            FileContents::Text(ref text) => Some(text),
+            FileContents::Bytes(_) | FileContents::Path(_) => None,
+        }
+    }
+}
+
+impl From<String> for FileContents {
+    fn from(text: String) -> Self {
+        FileContents::Text(text)
+    }
+}
+
+impl From<&str> for FileContents {
+    fn from(text: &str) -> Self {
+        FileContents::Text(text.into())
+    }
+}
+
+impl From<Vec<u8>> for FileContents {
+    fn from(bytes: Vec<u8>) -> Self {
+        FileContents::Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for FileContents {
+    fn from(bytes: &[u8]) -> Self {
+        FileContents::Bytes(bytes.into())
+    }
+}
+
+impl From<PathBuf> for FileContents {
+    fn from(path: PathBuf) -> Self {
+        FileContents::Path(path)
+    }
+}
+
+impl Serialize for FileContents {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        match *self {
+            FileContents::Text(ref text) => serializer.serialize_str(text),
+            FileContents::Bytes(ref bytes) => serializer.serialize_bytes(bytes),
+            FileContents::Path(ref path) => serializer.serialize_newtype_struct(
+                "$FormFilePath", path
+            ),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FileContents {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> StdResult<Self, D::Error> {
+        deserializer.deserialize_any(FileContentsVisitor)
+    }
+}
+
+/// Serde visitor for deserializing file contents from either a string
+/// or a byte sequence.
+#[derive(Debug, Clone, Copy, Default)]
+struct FileContentsVisitor;
+
+impl<'de> Visitor<'de> for FileContentsVisitor {
+    type Value = FileContents;
+
+    fn expecting(&self, formatter: &mut Formatter) -> FmtResult {
+        formatter.write_str("a string or a sequence of bytes")
+    }
+
+    fn visit_str<E: DeError>(self, v: &str) -> StdResult<Self::Value, E> {
+        Ok(FileContents::Text(v.into()))
+    }
+
+    fn visit_string<E: DeError>(self, v: String) -> StdResult<Self::Value, E> {
+        Ok(FileContents::Text(v))
+    }
+
+    fn visit_bytes<E: DeError>(self, v: &[u8]) -> StdResult<Self::Value, E> {
+        Ok(FileContents::Bytes(v.into()))
+    }
+
+    fn visit_byte_buf<E: DeError>(self, v: Vec<u8>) -> StdResult<Self::Value, E> {
+        Ok(FileContents::Bytes(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> StdResult<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+
+        Ok(FileContents::Bytes(bytes))
+    }
+}
+
 /// Takes a serializable value and turns into a multipart form.
 pub fn to_form<T: Serialize>(value: &T) -> Result<Form> {
     let mut serializer = FormSerializer::default();
@@ -78,19 +202,31 @@ struct FormSerializer {
     serializing_map: bool,
     /// Are we currently serializing a file part?
     serializing_file: bool,
+    /// Are we currently serializing the path of a file part?
+    serializing_path: bool,
     /// The current key when we are serializing a struct.
     current_key: Option<Cow<'static, str>>,
     /// The current file contents when we are serializing a file part.
-    current_file_contents: Option<Cow<'static, str>>,
+    current_file_contents: Option<PendingFile>,
     /// The result being built.
     form: Option<Form>,
 }
 
+/// The contents of a file part, before its file name is known.
+#[derive(Debug)]
+enum PendingFile {
+    /// In-memory contents.
+    Bytes(Cow<'static, [u8]>),
+    /// Path of a file to be streamed.
+    Path(PathBuf),
+}
+
 impl Default for FormSerializer {
     fn default() -> Self {
         FormSerializer {
             serializing_map: false,
             serializing_file: false,
+            serializing_path: false,
             current_key: None,
             current_file_contents: None,
             form: Some(Form::new()),
@@ -104,29 +240,41 @@ impl FormSerializer {
         where T: Into<Cow<'static, str>>
     {
         if self.serializing_map {
-            let string = string.into();
+            let value = string.into();
 
             // If a key already exists, we are a value, otherwise we are a key.
             match self.current_key.take() {
                 Some(key) => {
-                    if self.serializing_file {
+                    if self.serializing_path {
+                        let path = PathBuf::from(value.into_owned());
+                        self.current_file_contents.replace(PendingFile::Path(path));
+                        self.current_key.replace(key); // put it back
+                    } else if self.serializing_file {
                         match self.current_file_contents.take() {
                             Some(contents) => {
+                                let part = match contents {
+                                    PendingFile::Bytes(bytes) => Part::bytes(bytes),
+                                    PendingFile::Path(path) => Part::file(&path)
+                                        .map_err(|cause| Error::File { path, cause })?,
+                                };
                                 let form = self.form.take().expect("form should never be None");
-                                let part = Part::text(contents).file_name(string);
-                                self.form.replace(form.part(key, part));
+                                self.form.replace(form.part(key, part.file_name(value)));
                             }
                             None => {
-                                self.current_file_contents.replace(string);
+                                let contents = match value {
+                                    Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+                                    Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+                                };
+                                self.current_file_contents.replace(PendingFile::Bytes(contents));
                                 self.current_key.replace(key); // put it back
                             }
                         }
                     } else {
                         let form = self.form.take().expect("form should never be None");
-                        self.form.replace(form.text(key, string));
+                        self.form.replace(form.text(key, value));
                     }
                 }
-                None => self.current_key = Some(string),
+                None => self.current_key = Some(value),
             }
 
             Ok(())
@@ -141,7 +289,15 @@ impl FormSerializer {
     fn serialize_form_blob<T>(&mut self, blob: T) -> Result<()>
         where T: Into<Cow<'static, [u8]>>
     {
-        if self.serializing_map {
+        if self.serializing_file {
+            // Binary file contents; the file name must follow as a string.
+            if self.current_file_contents.is_some() {
+                Err(Error::custom("file name of form part must be a string"))
+            } else {
+                self.current_file_contents.replace(PendingFile::Bytes(blob.into()));
+                Ok(())
+            }
+        } else if self.serializing_map {
             // If a key already exists, we are a value, otherwise we are a key.
             self.current_key.take().map_or_else(
                 || Err(Error::custom(
@@ -256,10 +412,22 @@ impl Serializer for &mut FormSerializer {
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok> {
-        value.serialize(self)
+        if name != "$FormFilePath" {
+            return value.serialize(self);
+        }
+
+        if !self.serializing_file || self.current_file_contents.is_some() {
+            return Err(Error::custom("file path must be the contents of a file form part"));
+        }
+
+        self.serializing_path = true;
+        let result = value.serialize(&mut *self);
+        self.serializing_path = false;
+
+        result
     }
 
     fn serialize_newtype_variant<T: ?Sized + Serialize>(
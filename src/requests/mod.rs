@@ -2,14 +2,19 @@
 
 use std::borrow::Cow;
 use serde::{ Serialize, Deserialize };
-use reqwest::{ Method, header::HeaderMap };
+use reqwest::{ Method, Response, header::HeaderMap };
+use crate::error::Result;
 pub use submit::*;
 pub use status::*;
 pub use result::*;
+pub use version::*;
+pub use structure::*;
 
 pub mod submit;
 pub mod status;
 pub mod result;
+pub mod version;
+pub mod structure;
 
 /// What body, if any, should be sent with a request?
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -26,6 +31,19 @@ pub enum RequestBody<T> {
     Multipart(T),
 }
 
+impl<T> RequestBody<T> {
+    /// A short, human-readable description of how the body is encoded.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            RequestBody::None         => "none",
+            RequestBody::Json(_)      => "json",
+            RequestBody::Query(_)     => "query",
+            RequestBody::Form(_)      => "form",
+            RequestBody::Multipart(_) => "multipart",
+        }
+    }
+}
+
 impl<T> Default for RequestBody<T> {
     fn default() -> Self {
         RequestBody::None
@@ -34,7 +52,11 @@ impl<T> Default for RequestBody<T> {
 
 /// A RING API request.
 pub trait Request {
-    /// The type of the body for this request. TODO(H2CO3): default to `()`.
+    /// The type of the body for this request. Requests without a body,
+    /// such as plain `GET`s, should use `()`.
+    ///
+    /// TODO(H2CO3): default to `()` once associated type defaults are
+    /// stabilized (rust-lang/rust#29661); they are still feature-gated.
     type Body: Serialize;
 
     /// The "return type" of the request.
@@ -43,8 +65,17 @@ pub trait Request {
     /// The HTTP method ("verb") for the request.
     const METHOD: Method = Method::GET;
 
+    /// Whether sending the request several times has the same effect as
+    /// sending it once. Only idempotent requests are retried (unless the
+    /// retry policy opts in to retrying all requests) and cached. The
+    /// default decides by `METHOD`; requests which e.g. create a job
+    /// in response to a `GET` must override it.
+    fn is_idempotent() -> bool {
+        is_idempotent_method(&Self::METHOD)
+    }
+
     /// The endpoint: the part of the URL/path that follows the base URL.
-    fn endpoint(&self) -> Cow<str>;
+    fn endpoint(&self) -> Cow<'_, str>;
 
     /// Additional headers for this request.
     fn headers(&self) -> HeaderMap {
@@ -55,6 +86,13 @@ pub trait Request {
     fn body(&self) -> RequestBody<&Self::Body> {
         Default::default()
     }
+
+    /// Decodes the body of a successful response. The default
+    /// implementation parses it as JSON; requests for plain text
+    /// or binary data should override it.
+    fn decode(mut response: Response) -> Result<Self::Response> {
+        response.json().map_err(From::from)
+    }
 }
 
 impl<R: Request> Request for &R {
@@ -63,7 +101,11 @@ impl<R: Request> Request for &R {
 
     const METHOD: Method = R::METHOD;
 
-    fn endpoint(&self) -> Cow<str> {
+    fn is_idempotent() -> bool {
+        R::is_idempotent()
+    }
+
+    fn endpoint(&self) -> Cow<'_, str> {
         (**self).endpoint()
     }
 
@@ -74,6 +116,10 @@ impl<R: Request> Request for &R {
     fn body(&self) -> RequestBody<&Self::Body> {
         (**self).body()
     }
+
+    fn decode(response: Response) -> Result<Self::Response> {
+        R::decode(response)
+    }
 }
 
 impl<R: Request> Request for &mut R {
@@ -82,7 +128,11 @@ impl<R: Request> Request for &mut R {
 
     const METHOD: Method = R::METHOD;
 
-    fn endpoint(&self) -> Cow<str> {
+    fn is_idempotent() -> bool {
+        R::is_idempotent()
+    }
+
+    fn endpoint(&self) -> Cow<'_, str> {
         (**self).endpoint()
     }
 
@@ -93,4 +143,16 @@ impl<R: Request> Request for &mut R {
     fn body(&self) -> RequestBody<&Self::Body> {
         (**self).body()
     }
+
+    fn decode(response: Response) -> Result<Self::Response> {
+        R::decode(response)
+    }
+}
+
+/// Whether requests with the given HTTP method are idempotent by definition.
+pub(crate) fn is_idempotent_method(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
 }
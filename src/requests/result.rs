@@ -3,31 +3,394 @@
 use std::fmt::{ Display, Formatter, Result as FmtResult };
 use std::str::FromStr;
 use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::io::{ Read, Write, BufReader, BufWriter };
+use std::fs::File;
+use std::path::Path;
+use std::collections::{ HashMap, HashSet, BTreeSet };
 use serde::{
     ser::{ Serialize, Serializer },
-    de::{ Deserialize, Deserializer, Visitor, Error as DeError },
+    de::{
+        Deserialize, Deserializer, DeserializeSeed, Visitor, MapAccess, SeqAccess,
+        Error as DeError,
+    },
 };
-use serde_json::{ self, Value };
+use serde_json::{ self, Value, Map };
 use super::Request;
 use crate::{
-    settings::Settings,
+    settings::{ Settings, ChainId },
     job::{ JobId, JobStatus },
-    error::Error,
+    error::{ Error, Result as RingResult },
 };
 
 /// Request the result of a job
+///
+/// The RING API has no way of selecting which node and edge attributes
+/// are returned, so the full network (including coordinates, energies,
+/// etc.) is always transferred.
+///
+/// This also works for jobs with a `Partial` status, in which case the
+/// MSA-derived fields of nodes and edges are `None`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct RetrieveResult {
     /// The RING Job ID for which to retrieve the results.
     pub job_id: JobId,
+    /// The engine (output format) of the results. Default `D3`.
+    ///
+    /// **NOTE:** `RetrieveResultResponse` describes the shape of the `D3`
+    /// output. For other engines, use `RetrieveRawResult` instead.
+    #[serde(default)]
+    pub engine: ResultEngine,
+}
+
+impl RetrieveResult {
+    /// Convenience constructor.
+    /// Creates a request for the results in the default (`D3`) format.
+    pub fn new(job_id: JobId) -> Self {
+        RetrieveResult {
+            job_id,
+            engine: ResultEngine::default(),
+        }
+    }
 }
 
 impl Request for RetrieveResult {
     type Body = ();
     type Response = RetrieveResultResponse;
 
-    fn endpoint(&self) -> Cow<str> {
-        format!("/results/{}?engine=d3", self.job_id).into()
+    fn endpoint(&self) -> Cow<'_, str> {
+        format!("{}?engine={}", self.job_id.endpoint("results"), self.engine).into()
+    }
+}
+
+/// Request the result of a job in any format, without interpreting it.
+/// Useful for engines other than `D3`, whose output is not described by
+/// a typed response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RetrieveRawResult {
+    /// The RING Job ID for which to retrieve the results.
+    pub job_id: JobId,
+    /// The engine (output format) of the results.
+    #[serde(default)]
+    pub engine: ResultEngine,
+}
+
+impl Request for RetrieveRawResult {
+    type Body = ();
+    type Response = Value;
+
+    fn endpoint(&self) -> Cow<'_, str> {
+        format!("{}?engine={}", self.job_id.endpoint("results"), self.engine).into()
+    }
+}
+
+/// The engine, i.e. the output format, in which the results of a job are
+/// requested. The default is `D3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ResultEngine {
+    /// The D3.js-style network of nodes and edges.
+    #[serde(rename = "d3")]
+    D3,
+    /// The Cytoscape.js-style network of elements.
+    #[serde(rename = "cytoscape")]
+    Cytoscape,
+}
+
+impl ResultEngine {
+    /// The name of the engine in the query string, e.g. `d3`.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ResultEngine::D3        => "d3",
+            ResultEngine::Cytoscape => "cytoscape",
+        }
+    }
+}
+
+impl Default for ResultEngine {
+    fn default() -> Self {
+        ResultEngine::D3
+    }
+}
+
+impl Display for ResultEngine {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.pad(self.as_str())
+    }
+}
+
+/// Descriptive statistics of a set of values, e.g. a property of nodes.
+///
+/// For per-chain statistics, combine `Stats::from_values()` with
+/// `RetrieveResultResponse::nodes_in_chain()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    /// The number of values taken into account.
+    pub count: usize,
+    /// The smallest value.
+    pub min: f64,
+    /// The largest value.
+    pub max: f64,
+    /// The arithmetic mean.
+    pub mean: f64,
+    /// The median. For an even number of values, this is the mean of the
+    /// two middle ones.
+    pub median: f64,
+}
+
+impl Stats {
+    /// Computes the statistics of the finite values among `values`;
+    /// NaNs and infinities are ignored. Returns `None` if no values remain.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_values<I: IntoIterator<Item=f64>>(values: I) -> Option<Self> {
+        let mut sorted: Vec<_> = values.into_iter().filter(|x| x.is_finite()).collect();
+
+        sorted.sort_by(|a, b| a.partial_cmp(b).expect("finite values are comparable"));
+
+        let count = sorted.len();
+        let min = *sorted.first()?;
+        let max = *sorted.last()?;
+        let mean = sorted.iter().sum::<f64>() / count as f64;
+        let median = if count % 2 == 0 {
+            (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0
+        } else {
+            sorted[count / 2]
+        };
+
+        Some(Stats { count, min, max, mean, median })
+    }
+}
+
+/// All edges between a pair of residues, merged into one.
+/// Returned by `RetrieveResultResponse::merged_edges()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedEdge {
+    /// One of the residues, as in the first of the merged edges.
+    pub node_id_1: NodeId,
+    /// The other residue, as in the first of the merged edges.
+    pub node_id_2: NodeId,
+    /// The distinct interactions of the merged edges, in order of their
+    /// first appearance. The subtypes are as reported by each edge, so they
+    /// may be swapped relative to `node_id_1` and `node_id_2`.
+    pub interactions: Vec<Interaction>,
+    /// The smallest distance among the merged edges.
+    pub min_distance: f64,
+    /// The sum of the energies of the merged edges.
+    pub total_energy: f64,
+    /// The number of merged edges.
+    pub edge_count: usize,
+}
+
+impl MergedEdge {
+    /// Whether any of the merged edges has the given main type.
+    pub fn has_type(&self, main_type: InteractionMainType) -> bool {
+        self.interactions.iter().any(|interaction| interaction.main_type == main_type)
+    }
+}
+
+/// Identifies an edge by its endpoints and its interaction, regardless of
+/// its direction. The endpoints are ordered so that `node_id_1 <= node_id_2`,
+/// and the subtypes of the interaction are swapped along with them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeKey {
+    /// The lesser endpoint.
+    pub node_id_1: NodeId,
+    /// The greater endpoint.
+    pub node_id_2: NodeId,
+    /// The interaction, with `subtype_1` belonging to `node_id_1`.
+    pub interaction: Interaction,
+}
+
+impl From<&Edge> for EdgeKey {
+    fn from(edge: &Edge) -> Self {
+        if edge.node_id_1 <= edge.node_id_2 {
+            EdgeKey {
+                node_id_1: edge.node_id_1,
+                node_id_2: edge.node_id_2,
+                interaction: edge.interaction,
+            }
+        } else {
+            EdgeKey {
+                node_id_1: edge.node_id_2,
+                node_id_2: edge.node_id_1,
+                interaction: Interaction {
+                    main_type: edge.interaction.main_type,
+                    subtype_1: edge.interaction.subtype_2,
+                    subtype_2: edge.interaction.subtype_1,
+                },
+            }
+        }
+    }
+}
+
+/// The differences between two results, e.g. of the same structure
+/// computed with different settings.
+/// Returned by `RetrieveResultResponse::diff()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResultDiff {
+    /// Nodes which are only in the other result.
+    pub added_nodes: BTreeSet<NodeId>,
+    /// Nodes which are only in this result.
+    pub removed_nodes: BTreeSet<NodeId>,
+    /// Edges which are only in the other result.
+    pub added_edges: BTreeSet<EdgeKey>,
+    /// Edges which are only in this result.
+    pub removed_edges: BTreeSet<EdgeKey>,
+}
+
+impl ResultDiff {
+    /// Returns `true` if the two results have the same nodes and edges.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+/// A selection of the edges of a result matching all of the given criteria,
+/// created by `RetrieveResultResponse::query()`. E.g. the hydrogen bonds
+/// between chains `a` and `b` are selected by
+/// `result.query().main_type(InteractionMainType::HydrogenBond).between_chains(a, b)`.
+///
+/// Criteria are only evaluated when iterating, and setting one again
+/// replaces its previous value.
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeQuery<'a> {
+    /// The result whose edges are selected.
+    result: &'a RetrieveResultResponse,
+    /// Only edges of this main type.
+    main_type: Option<InteractionMainType>,
+    /// Only edges between these two chains, in either direction.
+    chains: Option<(ChainId, ChainId)>,
+    /// Only edges with one of their endpoints being this node.
+    node_id: Option<NodeId>,
+    /// Only edges at most this long.
+    max_distance: Option<f64>,
+}
+
+impl<'a> EdgeQuery<'a> {
+    /// Selects only edges of the given main type.
+    pub fn main_type(self, main_type: InteractionMainType) -> Self {
+        EdgeQuery { main_type: Some(main_type), ..self }
+    }
+
+    /// Selects only edges with one endpoint in each of the given chains.
+    /// If the two chains are the same, this selects intra-chain edges.
+    pub fn between_chains(self, chain_1: ChainId, chain_2: ChainId) -> Self {
+        EdgeQuery { chains: Some((chain_1, chain_2)), ..self }
+    }
+
+    /// Selects only edges with the given node as one of their endpoints.
+    pub fn involving(self, node_id: NodeId) -> Self {
+        EdgeQuery { node_id: Some(node_id), ..self }
+    }
+
+    /// Selects only edges whose distance is at most `max_distance`.
+    pub fn max_distance(self, max_distance: f64) -> Self {
+        EdgeQuery { max_distance: Some(max_distance), ..self }
+    }
+
+    /// Whether the edge matches all criteria.
+    #[allow(clippy::unnecessary_map_or)] // `Option::is_none_or()` needs Rust 1.82
+    pub fn matches(&self, edge: &Edge) -> bool {
+        let chain_1 = edge.node_id_1.chain_id;
+        let chain_2 = edge.node_id_2.chain_id;
+
+        self.main_type.map_or(true, |main_type| edge.interaction.main_type == main_type)
+            && self.chains.map_or(true, |(a, b)| {
+                (chain_1, chain_2) == (a, b) || (chain_1, chain_2) == (b, a)
+            })
+            && self.node_id.map_or(true, |id| edge.node_id_1 == id || edge.node_id_2 == id)
+            && self.max_distance.map_or(true, |max| edge.distance <= max)
+    }
+
+    /// Iterates over the matching edges, in the order of `edges`.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Edge> {
+        let query = *self;
+        self.result.edges.iter().filter(move |edge| query.matches(edge))
+    }
+
+    /// The matching edges, in the order of `edges`.
+    pub fn collect(&self) -> Vec<&'a Edge> {
+        self.iter().collect()
+    }
+
+    /// The number of matching edges.
+    pub fn count(&self) -> usize {
+        self.iter().count()
+    }
+}
+
+/// A serde seed for deserializing a result, which hands over its edges
+/// to a callback instead of storing them.
+#[derive(Debug, Clone, Copy)]
+struct StreamingResult<F> {
+    /// Called with each edge, in order.
+    on_edge: F,
+}
+
+impl<'de, F: FnMut(Edge)> DeserializeSeed<'de> for StreamingResult<F> {
+    type Value = RetrieveResultResponse;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(Edge)> Visitor<'de> for StreamingResult<F> {
+    type Value = RetrieveResultResponse;
+
+    fn expecting(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str("a RING result object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = Map::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            let value = if key == "edges" {
+                map.next_value_seed(EdgeSeq { on_edge: &mut self.on_edge })?;
+                Value::Array(Vec::new())
+            } else {
+                map.next_value()?
+            };
+
+            fields.insert(key, value);
+        }
+
+        serde_json::from_value(Value::Object(fields)).map_err(A::Error::custom)
+    }
+}
+
+/// A serde seed for deserializing the `edges` array of a result
+/// one element at a time.
+#[derive(Debug)]
+struct EdgeSeq<'a, F> {
+    /// Called with each edge, in order.
+    on_edge: &'a mut F,
+}
+
+impl<'de, 'a, F: FnMut(Edge)> DeserializeSeed<'de> for EdgeSeq<'a, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a, F: FnMut(Edge)> Visitor<'de> for EdgeSeq<'a, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str("an array of edges")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while let Some(edge) = seq.next_element()? {
+            (self.on_edge)(edge);
+        }
+
+        Ok(())
     }
 }
 
@@ -45,6 +408,9 @@ pub struct RetrieveResultResponse {
     /// If a file name has been supplied upon submission, it is captured here.
     #[serde(default, rename = "fileName", skip_serializing_if = "Option::is_none")]
     pub file_name: Option<String>,
+    /// The version of RING that processed the job, if the server reports it.
+    #[serde(default, rename = "version", alias = "ringVersion", skip_serializing_if = "Option::is_none")]
+    pub ring_version: Option<String>,
     /// The job settings are echoed back.
     #[serde(flatten, default)]
     pub settings: Settings,
@@ -54,6 +420,411 @@ pub struct RetrieveResultResponse {
     pub edges: Vec<Edge>,
 }
 
+impl RetrieveResultResponse {
+    /// Creates a completed result with no nodes or edges and default settings.
+    ///
+    /// There is deliberately no `Default` impl, since a result is meaningless
+    /// without a job ID. Together with `Node::new()` and `Edge::new()`, this
+    /// is mostly useful for building results by hand, e.g. in tests.
+    pub fn empty(job_id: JobId) -> Self {
+        RetrieveResultResponse {
+            job_id,
+            status: JobStatus::Complete,
+            pdb_id: None,
+            file_name: None,
+            ring_version: None,
+            settings: Settings::default(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Parses a result from JSON, e.g. one that was saved earlier using
+    /// `to_writer()`. The reader should be buffered.
+    pub fn from_reader<R: Read>(reader: R) -> RingResult<Self> {
+        serde_json::from_reader(reader).map_err(From::from)
+    }
+
+    /// Loads a result from a JSON file, e.g. one that was saved earlier
+    /// using `to_file()`. Doesn't require a `Client`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> RingResult<Self> {
+        let file = File::open(&path)
+            .map_err(|cause| Error::File { path: path.as_ref().into(), cause })?;
+
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// Writes the result as JSON, in the same format as the server sends it.
+    pub fn to_writer<W: Write>(&self, writer: W) -> RingResult<()> {
+        serde_json::to_writer(writer, self).map_err(From::from)
+    }
+
+    /// Saves the result as JSON to a file, creating or truncating it.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> RingResult<()> {
+        let file_error = |cause| Error::File { path: path.as_ref().into(), cause };
+        let mut writer = BufWriter::new(File::create(&path).map_err(file_error)?);
+
+        self.to_writer(&mut writer)?;
+        writer.flush().map_err(file_error)
+    }
+
+    /// Parses a JSON result from `reader`, passing each edge to `on_edge`
+    /// as soon as it is parsed instead of collecting them into `edges`,
+    /// which is left empty in the returned value.
+    ///
+    /// This keeps memory usage bounded by the size of a single edge, which
+    /// matters for huge complexes with hundreds of thousands of edges.
+    /// The tradeoffs compared to the eager `RetrieveResult` are that the
+    /// edges can only be visited once and in the order the server sent
+    /// them, and that everything other than the edges, including `nodes`,
+    /// is still loaded into memory. The reader should be buffered.
+    pub fn from_reader_streaming<R, F>(reader: R, on_edge: F) -> RingResult<Self>
+        where R: Read,
+              F: FnMut(Edge),
+    {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let result = StreamingResult { on_edge }.deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(result)
+    }
+
+    /// The fraction of residues which participate in at least one interaction,
+    /// according to their `degree`. Returns 0 if there are no nodes at all.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn interacting_residue_fraction(&self) -> f64 {
+        if self.nodes.is_empty() {
+            return 0.0;
+        }
+
+        let interacting = self.nodes.iter().filter(|node| node.degree > 0).count();
+
+        interacting as f64 / self.nodes.len() as f64
+    }
+
+    /// Summary statistics of the solvent accessibility of all nodes.
+    /// Returns `None` if there are no nodes with a finite accessibility.
+    pub fn accessibility_stats(&self) -> Option<Stats> {
+        Stats::from_values(self.nodes.iter().map(|node| node.accessibility))
+    }
+
+    /// Summary statistics of the B-factor of the alpha carbon of all nodes.
+    /// Returns `None` if there are no nodes with a finite B-factor.
+    pub fn bfactor_stats(&self) -> Option<Stats> {
+        Stats::from_values(self.nodes.iter().map(|node| node.bfactor_ca))
+    }
+
+    /// Residues which don't participate in any interaction. These often
+    /// indicate gaps or other problems with the structure.
+    pub fn isolated_nodes(&self) -> Vec<&Node> {
+        self.nodes.iter().filter(|node| node.degree == 0).collect()
+    }
+
+    /// Iterates over the nodes, in order. Prefer this over accessing `nodes`
+    /// directly, since it doesn't depend on how the nodes are stored.
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> + '_ {
+        self.nodes.iter()
+    }
+
+    /// Iterates over the edges, in order. Prefer this over accessing `edges`
+    /// directly, since it doesn't depend on how the edges are stored.
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> + '_ {
+        self.edges.iter()
+    }
+
+    /// Starts a query over the edges, initially selecting all of them.
+    /// See `EdgeQuery` for the available criteria.
+    pub fn query(&self) -> EdgeQuery<'_> {
+        EdgeQuery {
+            result: self,
+            main_type: None,
+            chains: None,
+            node_id: None,
+            max_distance: None,
+        }
+    }
+
+    /// Consumes the result, yielding its nodes in order.
+    pub fn into_nodes(self) -> impl Iterator<Item = Node> {
+        self.nodes.into_iter()
+    }
+
+    /// Consumes the result, yielding its edges in order.
+    pub fn into_edges(self) -> impl Iterator<Item = Edge> {
+        self.edges.into_iter()
+    }
+
+    /// Builds a map from node IDs to nodes, for repeated lookups by ID.
+    /// If several nodes have the same ID, the last one wins.
+    pub fn node_index(&self) -> HashMap<NodeId, &Node> {
+        self.nodes.iter().map(|node| (node.node_id, node)).collect()
+    }
+
+    /// Finds the node with the given ID. This is a linear search, so when
+    /// looking up many nodes, build a `node_index()` once instead.
+    pub fn node(&self, id: &NodeId) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.node_id == *id)
+    }
+
+    /// The IDs of all chains which have at least one node.
+    pub fn chains(&self) -> BTreeSet<ChainId> {
+        self.nodes.iter().map(|node| node.node_id.chain_id).collect()
+    }
+
+    /// The nodes of the given chain, in the order they appear in `nodes`.
+    pub fn nodes_in_chain(&self, chain_id: ChainId) -> impl Iterator<Item=&Node> + '_ {
+        self.nodes.iter().filter(move |node| node.node_id.chain_id == chain_id)
+    }
+
+    /// The nodes of the given chain in sequence order, i.e. sorted by their
+    /// position, then by their insertion code (residues without one first).
+    /// Nodes at the same position with the same insertion code retain their
+    /// relative order.
+    pub fn nodes_in_chain_sorted(&self, chain_id: ChainId) -> Vec<&Node> {
+        let mut nodes: Vec<_> = self.nodes_in_chain(chain_id).collect();
+
+        nodes.sort_by_key(|node| {
+            let id = node.node_id;
+            (id.position, id.has_insertion_code(), id.insertion_code)
+        });
+
+        nodes
+    }
+
+    /// The nodes whose coordinates are at most `radius` away from those of
+    /// the node with ID `center`, excluding that node itself, in the order
+    /// they appear in `nodes`. Empty if there is no node with ID `center`.
+    pub fn residues_within(&self, center: &NodeId, radius: f64) -> Vec<&Node> {
+        match self.node(center) {
+            Some(origin) => self.nodes
+                .iter()
+                .filter(|node| node.node_id != *center && node.distance_to(origin) <= radius)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Extracts the part of the network on the given chain: its nodes, and
+    /// the edges between them. If `keep_inter_chain` is true, edges between
+    /// this chain and other chains are retained as well, even though their
+    /// other endpoint is not among the nodes. The `degree` of the nodes is
+    /// recomputed to match the retained edges.
+    pub fn subgraph_for_chain(&self, chain_id: ChainId, keep_inter_chain: bool) -> Self {
+        let on_chain = |id: &NodeId| id.chain_id == chain_id;
+        let edges = self.edges
+            .iter()
+            .filter(|edge| {
+                let first = on_chain(&edge.node_id_1);
+                let second = on_chain(&edge.node_id_2);
+
+                (first && second) || (keep_inter_chain && (first || second))
+            })
+            .cloned()
+            .collect();
+        let mut subgraph = RetrieveResultResponse {
+            job_id: self.job_id.clone(),
+            status: self.status,
+            pdb_id: self.pdb_id.clone(),
+            file_name: self.file_name.clone(),
+            ring_version: self.ring_version.clone(),
+            settings: self.settings,
+            nodes: self.nodes_in_chain(chain_id).cloned().collect(),
+            edges,
+        };
+        let degrees = subgraph.recompute_degrees();
+
+        for node in &mut subgraph.nodes {
+            node.degree = degrees.get(&node.node_id).cloned().unwrap_or(0);
+        }
+
+        subgraph
+    }
+
+    /// Counts the edges incident to each node, based on `edges` alone.
+    ///
+    /// Every node in `nodes` is present in the map, even if it has no
+    /// edges; so is every endpoint of an edge, even if it is not in `nodes`.
+    /// A self-loop counts twice towards the degree of its node.
+    pub fn recompute_degrees(&self) -> HashMap<NodeId, usize> {
+        let mut degrees: HashMap<_, _> = self.nodes
+            .iter()
+            .map(|node| (node.node_id, 0))
+            .collect();
+
+        for edge in &self.edges {
+            *degrees.entry(edge.node_id_1).or_insert(0) += 1;
+            *degrees.entry(edge.node_id_2).or_insert(0) += 1;
+        }
+
+        degrees
+    }
+
+    /// Checks that the `degree` of every node agrees with the number of its
+    /// edges, as computed by `recompute_degrees()`. The error lists every
+    /// node whose degree doesn't match.
+    pub fn verify_degrees(&self) -> RingResult<()> {
+        let degrees = self.recompute_degrees();
+        let mismatches: Vec<_> = self.nodes
+            .iter()
+            .filter_map(|node| {
+                let actual = degrees.get(&node.node_id).cloned().unwrap_or(0);
+
+                if node.degree == actual {
+                    None
+                } else {
+                    Some(format!("{} (expected {}, found {})", node.node_id, node.degree, actual))
+                }
+            })
+            .collect();
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation(format!(
+                "inconsistent node degrees: {}", mismatches.join(", ")
+            )))
+        }
+    }
+
+    /// Yields the residues connected to the given one by an edge, in either
+    /// direction. A residue is yielded once per edge, so it may appear
+    /// several times if the two residues interact in multiple ways;
+    /// use `distinct_neighbors()` if that is not desired.
+    pub fn neighbors(&self, id: &NodeId) -> impl Iterator<Item = &NodeId> + '_ {
+        let node_id = *id;
+        self.edges.iter().filter_map(move |edge| edge.other(&node_id))
+    }
+
+    /// Like `neighbors()`, but each residue is only returned once,
+    /// in the order of its first occurrence.
+    pub fn distinct_neighbors(&self, id: &NodeId) -> Vec<&NodeId> {
+        let mut seen = HashSet::new();
+        self.neighbors(id).filter(|&other| seen.insert(other)).collect()
+    }
+
+    /// Merges the edges between each pair of residues into a single edge,
+    /// regardless of the direction of the original edges. See `MergedEdge`
+    /// for how the properties of the edges are aggregated. The merged edges
+    /// are in the order of the first edge of each pair in `edges`.
+    pub fn merged_edges(&self) -> Vec<MergedEdge> {
+        let mut merged = Vec::new();
+        let mut indices: HashMap<(NodeId, NodeId), usize> = HashMap::new();
+
+        for edge in &self.edges {
+            let pair = (edge.node_id_1, edge.node_id_2);
+            let reversed = (edge.node_id_2, edge.node_id_1);
+
+            match indices.get(&pair).or_else(|| indices.get(&reversed)) {
+                Some(&i) => {
+                    let entry: &mut MergedEdge = &mut merged[i];
+
+                    if !entry.interactions.contains(&edge.interaction) {
+                        entry.interactions.push(edge.interaction);
+                    }
+
+                    entry.min_distance = entry.min_distance.min(edge.distance);
+                    entry.total_energy += edge.energy;
+                    entry.edge_count += 1;
+                }
+                None => {
+                    indices.insert(pair, merged.len());
+                    merged.push(MergedEdge {
+                        node_id_1: edge.node_id_1,
+                        node_id_2: edge.node_id_2,
+                        interactions: vec![edge.interaction],
+                        min_distance: edge.distance,
+                        total_energy: edge.energy,
+                        edge_count: 1,
+                    });
+                }
+            }
+        }
+
+        merged
+    }
+
+    /// Compares the nodes and edges of this result to those of `other`.
+    ///
+    /// Edges are compared by `EdgeKey`, i.e. by their endpoints and their
+    /// interaction, ignoring direction, distance, energy, etc. The order of
+    /// `nodes` and `edges` doesn't matter, and duplicates count once.
+    pub fn diff(&self, other: &Self) -> ResultDiff {
+        let old_nodes: BTreeSet<_> = self.nodes.iter().map(Node::key).collect();
+        let new_nodes: BTreeSet<_> = other.nodes.iter().map(Node::key).collect();
+        let old_edges: BTreeSet<_> = self.edges.iter().map(Edge::key).collect();
+        let new_edges: BTreeSet<_> = other.edges.iter().map(Edge::key).collect();
+
+        ResultDiff {
+            added_nodes: new_nodes.difference(&old_nodes).copied().collect(),
+            removed_nodes: old_nodes.difference(&new_nodes).copied().collect(),
+            added_edges: new_edges.difference(&old_edges).copied().collect(),
+            removed_edges: old_edges.difference(&new_edges).copied().collect(),
+        }
+    }
+
+    /// The edges sorted by decreasing energy, i.e. strongest first.
+    /// Edges of equal energy retain their order in `edges`, and edges
+    /// whose energy is NaN come last.
+    pub fn edges_by_energy(&self) -> Vec<&Edge> {
+        let mut edges: Vec<_> = self.edges.iter().collect();
+
+        edges.sort_by(|lhs, rhs| {
+            lhs.energy.is_nan().cmp(&rhs.energy.is_nan()).then_with(
+                || rhs.energy.partial_cmp(&lhs.energy).unwrap_or(Ordering::Equal)
+            )
+        });
+
+        edges
+    }
+
+    /// The `n` edges with the highest energy, in the order of
+    /// `edges_by_energy()`. Returns all edges if there are fewer than `n`.
+    pub fn strongest_edges(&self, n: usize) -> Vec<&Edge> {
+        let mut edges = self.edges_by_energy();
+        edges.truncate(n);
+        edges
+    }
+
+    /// Counts the edges of each main interaction type.
+    /// Types without any edges are absent from the map.
+    pub fn interaction_counts(&self) -> HashMap<InteractionMainType, usize> {
+        let mut counts = HashMap::new();
+
+        for edge in &self.edges {
+            *counts.entry(edge.interaction.main_type).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    /// Counts the edges of each pair of subtypes, e.g. `(MC, SC)`.
+    /// The pairs are ordered, i.e. `(MC, SC)` and `(SC, MC)` are distinct.
+    pub fn subtype_counts(&self) -> HashMap<(InteractionSubType, InteractionSubType), usize> {
+        let mut counts = HashMap::new();
+
+        for edge in &self.edges {
+            let key = (edge.interaction.subtype_1, edge.interaction.subtype_2);
+            *counts.entry(key).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    /// Checks the result for inconsistencies which the deserializer
+    /// can't catch, e.g. directional fields on the wrong interaction type.
+    pub fn validate(&self) -> RingResult<()> {
+        self.edges.iter().try_for_each(Edge::validate_roles)
+    }
+}
+
+impl FromStr for RetrieveResultResponse {
+    type Err = Error;
+
+    /// Parses a result from JSON, e.g. cached data or a test fixture.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s).map_err(From::from)
+    }
+}
+
 /// A node in the interaction graph.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Node {
@@ -62,7 +833,7 @@ pub struct Node {
     pub node_id: NodeId,
     /// The name/ID of the chain this residue belongs in.
     #[serde(rename = "Chain")]
-    pub chain_id: char,
+    pub chain_id: ChainId,
     /// The position of the residue inside the sequence, according to PDB.
     /// **NOTE:** this sometimes is 0 or a **negative** integer.
     #[serde(rename = "Position")]
@@ -95,39 +866,106 @@ pub struct Node {
     #[serde(rename = "Rapdf", default, skip_serializing_if = "Option::is_none")]
     pub rapdf_energy: Option<f64>,
     /// Only for letting RINanylezer/StructureViz and Chimera love each other.
-    #[serde(rename = "pdbFileName")]
-    pub pdb_file_name: String,
+    #[serde(rename = "pdbFileName", default, skip_serializing_if = "Option::is_none")]
+    pub pdb_file_name: Option<String>,
     /// Shannon entropy computed from a multiple alignment (MSA=true).
     #[serde(rename = "Entropy", default, skip_serializing_if = "Option::is_none")]
     pub entropy: Option<f64>,
     /// Cumulative mutual entropy. Yes, it's incorrectly called "comulative"
-    /// in the JSON returned by the API.
-    #[serde(rename = "MIcomulative", default, skip_serializing_if = "Option::is_none")]
+    /// in the JSON returned by the API. The correct spelling is accepted too,
+    /// in case a future version of RING fixes the typo.
+    #[serde(rename = "MIcomulative", alias = "MIcumulative",
+            default, skip_serializing_if = "Option::is_none")]
     pub cumul_mutual_entropy: Option<f64>,
+    /// Any other fields sent by the server which this crate doesn't model.
+    #[serde(flatten, default, skip_serializing_if = "Map::is_empty")]
+    pub extra: Map<String, Value>,
+}
+
+impl Node {
+    /// Creates a node with the given ID. The chain, position and residue are
+    /// taken from the ID; coordinates and all numeric properties are zero,
+    /// the secondary structure is `DsspStructure::None`, and optional fields
+    /// are `None`. Fields are public, so they can be adjusted afterwards.
+    pub fn new(node_id: NodeId) -> Self {
+        Node {
+            node_id,
+            chain_id: node_id.chain_id,
+            position: node_id.position,
+            residue: node_id.residue,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            dssp_structure: DsspStructure::None,
+            degree: 0,
+            accessibility: 0.0,
+            bfactor_ca: 0.0,
+            tap_energy: None,
+            rapdf_energy: None,
+            pdb_file_name: None,
+            entropy: None,
+            cumul_mutual_entropy: None,
+            extra: Map::new(),
+        }
+    }
+
+    /// A key identifying the node, for use in sorted or hashed collections,
+    /// which `Node` itself can't be in, since it has floating-point fields.
+    pub fn key(&self) -> NodeId {
+        self.node_id
+    }
+
+    /// The coordinates of the node as an `[x, y, z]` array.
+    pub fn coords(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    /// The Euclidean distance between the coordinates of two nodes.
+    pub fn distance_to(&self, other: &Node) -> f64 {
+        let dx = self.x - other.x;
+        let dy = self.y - other.y;
+        let dz = self.z - other.z;
+
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
 }
 
 /// A structured Node ID.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct NodeId {
-    /// The chain ID letter found in the PDB structure.
-    pub chain_id: char,
+    /// The chain ID found in the PDB structure.
+    pub chain_id: ChainId,
     /// The PDB position index. May be negative.
     pub position: isize,
-    /// The PDB insertion code.
+    /// The PDB insertion code. RING usually writes `_` if there is none,
+    /// but sometimes leaves the field empty, which is represented by
+    /// `NodeId::BLANK_INSERTION_CODE` (a space).
     pub insertion_code: char,
     /// The amino acid residue kind.
     pub residue: Residue,
 }
 
+impl NodeId {
+    /// The insertion code of node IDs whose insertion code field is empty,
+    /// e.g. `A:100::ALA`. Such IDs are displayed with an empty field again.
+    pub const BLANK_INSERTION_CODE: char = ' ';
+
+    /// Whether the residue has an actual insertion code, i.e. one that is
+    /// neither `_` nor blank.
+    pub fn has_insertion_code(&self) -> bool {
+        self.insertion_code != '_' && self.insertion_code != Self::BLANK_INSERTION_CODE
+    }
+}
+
 impl Display for NodeId {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        write!(
-            f, "{}:{}:{}:{}",
-            self.chain_id,
-            self.position,
-            self.insertion_code,
-            self.residue,
-        )
+        write!(f, "{}:{}:", self.chain_id, self.position)?;
+
+        if self.insertion_code != Self::BLANK_INSERTION_CODE {
+            write!(f, "{}", self.insertion_code)?;
+        }
+
+        write!(f, ":{}", self.residue)
     }
 }
 
@@ -141,7 +979,11 @@ impl FromStr for NodeId {
             Ok(NodeId {
                 chain_id:       v[0].parse()?,
                 position:       v[1].parse()?,
-                insertion_code: v[2].parse()?,
+                insertion_code: if v[2].is_empty() {
+                    Self::BLANK_INSERTION_CODE
+                } else {
+                    v[2].parse()?
+                },
                 residue:        v[3].parse()?,
             })
         } else {
@@ -281,12 +1123,353 @@ pub enum Residue {
     Unknown,
 }
 
+impl Residue {
+    /// All residues, in declaration order.
+    pub fn all() -> &'static [Residue] {
+        &[
+            Residue::Alanine,
+            Residue::Arginine,
+            Residue::Asparagine,
+            Residue::AsparticAcid,
+            Residue::Cysteine,
+            Residue::GlutamicAcid,
+            Residue::Glutamine,
+            Residue::Glycine,
+            Residue::Homocysteine,
+            Residue::Histidine,
+            Residue::Homoserine,
+            Residue::Isoleucine,
+            Residue::Leucine,
+            Residue::Lysine,
+            Residue::Methionine,
+            Residue::Norleucine,
+            Residue::Norvaline,
+            Residue::Ornithine,
+            Residue::Penicillamine,
+            Residue::Phenylalanine,
+            Residue::Proline,
+            Residue::Pyrrolysine,
+            Residue::Selenocysteine,
+            Residue::Serine,
+            Residue::Threonine,
+            Residue::Tryptophan,
+            Residue::Tyrosine,
+            Residue::Valine,
+            Residue::AsparagineOrAsparticAcid,
+            Residue::GlutamineOrGlutamicAcid,
+            Residue::LeucineOrIsoleucine,
+            Residue::Unknown,
+        ]
+    }
+
+    /// Returns the IUPAC one-letter code of the residue.
+    ///
+    /// Ambiguity codes are `B` (Asx), `Z` (Glx) and `J` (Xle).
+    /// Non-standard residues without a code of their own, such as
+    /// norleucine or ornithine, map to `X`, just like `Unknown`.
+    pub fn one_letter(&self) -> char {
+        match *self {
+            Residue::Alanine                  => 'A',
+            Residue::Arginine                 => 'R',
+            Residue::Asparagine               => 'N',
+            Residue::AsparticAcid             => 'D',
+            Residue::Cysteine                 => 'C',
+            Residue::GlutamicAcid             => 'E',
+            Residue::Glutamine                => 'Q',
+            Residue::Glycine                  => 'G',
+            Residue::Histidine                => 'H',
+            Residue::Isoleucine               => 'I',
+            Residue::Leucine                  => 'L',
+            Residue::Lysine                   => 'K',
+            Residue::Methionine               => 'M',
+            Residue::Phenylalanine            => 'F',
+            Residue::Proline                  => 'P',
+            Residue::Pyrrolysine              => 'O',
+            Residue::Selenocysteine           => 'U',
+            Residue::Serine                   => 'S',
+            Residue::Threonine                => 'T',
+            Residue::Tryptophan               => 'W',
+            Residue::Tyrosine                 => 'Y',
+            Residue::Valine                   => 'V',
+            Residue::AsparagineOrAsparticAcid => 'B',
+            Residue::GlutamineOrGlutamicAcid  => 'Z',
+            Residue::LeucineOrIsoleucine      => 'J',
+            Residue::Homocysteine
+            | Residue::Homoserine
+            | Residue::Norleucine
+            | Residue::Norvaline
+            | Residue::Ornithine
+            | Residue::Penicillamine
+            | Residue::Unknown                => 'X',
+        }
+    }
+
+    /// Returns the three-letter PDB code of the residue, e.g. `ALA`.
+    /// This is also how the residue is serialized and displayed.
+    pub fn three_letter(&self) -> &'static str {
+        match *self {
+            Residue::Alanine                  => "ALA",
+            Residue::Arginine                 => "ARG",
+            Residue::Asparagine               => "ASN",
+            Residue::AsparticAcid             => "ASP",
+            Residue::Cysteine                 => "CYS",
+            Residue::GlutamicAcid             => "GLU",
+            Residue::Glutamine                => "GLN",
+            Residue::Glycine                  => "GLY",
+            Residue::Homocysteine             => "HCY",
+            Residue::Histidine                => "HIS",
+            Residue::Homoserine               => "HSE",
+            Residue::Isoleucine               => "ILE",
+            Residue::Leucine                  => "LEU",
+            Residue::Lysine                   => "LYS",
+            Residue::Methionine               => "MET",
+            Residue::Norleucine               => "NLE",
+            Residue::Norvaline                => "NVA",
+            Residue::Ornithine                => "ORN",
+            Residue::Penicillamine            => "PEN",
+            Residue::Phenylalanine            => "PHE",
+            Residue::Proline                  => "PRO",
+            Residue::Pyrrolysine              => "PYL",
+            Residue::Selenocysteine           => "SEC",
+            Residue::Serine                   => "SER",
+            Residue::Threonine                => "THR",
+            Residue::Tryptophan               => "TRP",
+            Residue::Tyrosine                 => "TYR",
+            Residue::Valine                   => "VAL",
+            Residue::AsparagineOrAsparticAcid => "ASX",
+            Residue::GlutamineOrGlutamicAcid  => "GLX",
+            Residue::LeucineOrIsoleucine      => "XLE",
+            Residue::Unknown                  => "XAA",
+        }
+    }
+
+    /// Returns the human-readable name of the residue, e.g. `Aspartic acid`.
+    pub fn full_name(&self) -> &'static str {
+        match *self {
+            Residue::Alanine                  => "Alanine",
+            Residue::Arginine                 => "Arginine",
+            Residue::Asparagine               => "Asparagine",
+            Residue::AsparticAcid             => "Aspartic acid",
+            Residue::Cysteine                 => "Cysteine",
+            Residue::GlutamicAcid             => "Glutamic acid",
+            Residue::Glutamine                => "Glutamine",
+            Residue::Glycine                  => "Glycine",
+            Residue::Homocysteine             => "Homocysteine",
+            Residue::Histidine                => "Histidine",
+            Residue::Homoserine               => "Homoserine",
+            Residue::Isoleucine               => "Isoleucine",
+            Residue::Leucine                  => "Leucine",
+            Residue::Lysine                   => "Lysine",
+            Residue::Methionine               => "Methionine",
+            Residue::Norleucine               => "Norleucine",
+            Residue::Norvaline                => "Norvaline",
+            Residue::Ornithine                => "Ornithine",
+            Residue::Penicillamine            => "Penicillamine",
+            Residue::Phenylalanine            => "Phenylalanine",
+            Residue::Proline                  => "Proline",
+            Residue::Pyrrolysine              => "Pyrrolysine",
+            Residue::Selenocysteine           => "Selenocysteine",
+            Residue::Serine                   => "Serine",
+            Residue::Threonine                => "Threonine",
+            Residue::Tryptophan               => "Tryptophan",
+            Residue::Tyrosine                 => "Tyrosine",
+            Residue::Valine                   => "Valine",
+            Residue::AsparagineOrAsparticAcid => "Asparagine or aspartic acid",
+            Residue::GlutamineOrGlutamicAcid  => "Glutamine or glutamic acid",
+            Residue::LeucineOrIsoleucine      => "Leucine or isoleucine",
+            Residue::Unknown                  => "Unknown",
+        }
+    }
+
+    /// Whether this is one of the 20 standard amino acids encoded by the
+    /// universal genetic code. Selenocysteine and pyrrolysine, other
+    /// non-standard residues, ambiguity codes and `Unknown` are not.
+    pub fn is_standard(&self) -> bool {
+        matches!(
+            *self,
+            Residue::Alanine
+            | Residue::Arginine
+            | Residue::Asparagine
+            | Residue::AsparticAcid
+            | Residue::Cysteine
+            | Residue::GlutamicAcid
+            | Residue::Glutamine
+            | Residue::Glycine
+            | Residue::Histidine
+            | Residue::Isoleucine
+            | Residue::Leucine
+            | Residue::Lysine
+            | Residue::Methionine
+            | Residue::Phenylalanine
+            | Residue::Proline
+            | Residue::Serine
+            | Residue::Threonine
+            | Residue::Tryptophan
+            | Residue::Tyrosine
+            | Residue::Valine
+        )
+    }
+
+    /// Maps the residue to the most similar standard amino acid, or to
+    /// `Unknown` if there is no single such amino acid. Standard residues
+    /// map to themselves.
+    ///
+    /// Non-standard residues map to the standard residue they are derived
+    /// from or most closely resemble structurally: selenocysteine,
+    /// homocysteine and penicillamine to cysteine, pyrrolysine and ornithine
+    /// to lysine, homoserine to serine, norleucine to leucine, and norvaline
+    /// to valine. Ambiguity codes (`Asx`, `Glx`, `Xle`) map to `Unknown`,
+    /// since picking either alternative would be a guess.
+    pub fn canonicalize(&self) -> Residue {
+        match *self {
+            Residue::Selenocysteine
+            | Residue::Homocysteine
+            | Residue::Penicillamine            => Residue::Cysteine,
+            Residue::Pyrrolysine
+            | Residue::Ornithine                => Residue::Lysine,
+            Residue::Homoserine                 => Residue::Serine,
+            Residue::Norleucine                 => Residue::Leucine,
+            Residue::Norvaline                  => Residue::Valine,
+            Residue::AsparagineOrAsparticAcid
+            | Residue::GlutamineOrGlutamicAcid
+            | Residue::LeucineOrIsoleucine
+            | Residue::Unknown                  => Residue::Unknown,
+            standard                            => standard,
+        }
+    }
+
+    /// Whether the side chain is hydrophobic. Besides the standard
+    /// aliphatic and aromatic hydrophobic residues, this includes the
+    /// non-standard norleucine and norvaline, as well as `Xle`, since both
+    /// of its alternatives are hydrophobic. Other ambiguity codes and
+    /// `Unknown` are not considered hydrophobic.
+    pub fn is_hydrophobic(&self) -> bool {
+        matches!(
+            *self,
+            Residue::Alanine
+            | Residue::Cysteine
+            | Residue::Isoleucine
+            | Residue::Leucine
+            | Residue::Methionine
+            | Residue::Norleucine
+            | Residue::Norvaline
+            | Residue::Phenylalanine
+            | Residue::Proline
+            | Residue::Tryptophan
+            | Residue::Valine
+            | Residue::LeucineOrIsoleucine
+        )
+    }
+
+    /// Whether the side chain carries a net charge at pH 7.
+    /// Equivalent to `self.charge_at_ph7() != 0`.
+    pub fn is_charged(&self) -> bool {
+        self.charge_at_ph7() != 0
+    }
+
+    /// The net charge of the side chain at pH 7. Histidine is considered
+    /// neutral, since its side chain is mostly unprotonated at this pH.
+    /// `Asx` and `Glx` yield 0, because only one of their alternatives
+    /// is charged, and so does `Unknown`.
+    pub fn charge_at_ph7(&self) -> i8 {
+        match *self {
+            Residue::Arginine | Residue::Lysine | Residue::Ornithine => 1,
+            Residue::AsparticAcid | Residue::GlutamicAcid => -1,
+            _ => 0,
+        }
+    }
+
+    /// Whether the side chain contains an aromatic ring.
+    pub fn is_aromatic(&self) -> bool {
+        matches!(
+            *self,
+            Residue::Histidine
+            | Residue::Phenylalanine
+            | Residue::Tryptophan
+            | Residue::Tyrosine
+        )
+    }
+
+    /// The average molecular weight of the free amino acid, in g/mol.
+    /// Returns `None` for `Asx`, `Glx` and `Unknown`, whose weight is not
+    /// determined. (`Xle` does have a weight, because leucine and
+    /// isoleucine are isomers.)
+    pub fn molecular_weight(&self) -> Option<f64> {
+        Some(match *self {
+            Residue::Alanine                  =>  89.09,
+            Residue::Arginine                 => 174.20,
+            Residue::Asparagine               => 132.12,
+            Residue::AsparticAcid             => 133.10,
+            Residue::Cysteine                 => 121.16,
+            Residue::GlutamicAcid             => 147.13,
+            Residue::Glutamine                => 146.15,
+            Residue::Glycine                  =>  75.07,
+            Residue::Homocysteine             => 135.19,
+            Residue::Histidine                => 155.16,
+            Residue::Homoserine               => 119.12,
+            Residue::Isoleucine               => 131.17,
+            Residue::Leucine                  => 131.17,
+            Residue::Lysine                   => 146.19,
+            Residue::Methionine               => 149.21,
+            Residue::Norleucine               => 131.17,
+            Residue::Norvaline                => 117.15,
+            Residue::Ornithine                => 132.16,
+            Residue::Penicillamine            => 149.21,
+            Residue::Phenylalanine            => 165.19,
+            Residue::Proline                  => 115.13,
+            Residue::Pyrrolysine              => 255.32,
+            Residue::Selenocysteine           => 168.06,
+            Residue::Serine                   => 105.09,
+            Residue::Threonine                => 119.12,
+            Residue::Tryptophan               => 204.23,
+            Residue::Tyrosine                 => 181.19,
+            Residue::Valine                   => 117.15,
+            Residue::LeucineOrIsoleucine      => 131.17,
+            Residue::AsparagineOrAsparticAcid
+            | Residue::GlutamineOrGlutamicAcid
+            | Residue::Unknown                => return None,
+        })
+    }
+
+    /// Parses an IUPAC one-letter code (case-insensitively).
+    /// This is the inverse of `one_letter()`, except that `X` always
+    /// yields `Unknown`. Returns `None` for letters which aren't a code.
+    pub fn from_one_letter(c: char) -> Option<Self> {
+        Some(match c.to_ascii_uppercase() {
+            'A' => Residue::Alanine,
+            'R' => Residue::Arginine,
+            'N' => Residue::Asparagine,
+            'D' => Residue::AsparticAcid,
+            'C' => Residue::Cysteine,
+            'E' => Residue::GlutamicAcid,
+            'Q' => Residue::Glutamine,
+            'G' => Residue::Glycine,
+            'H' => Residue::Histidine,
+            'I' => Residue::Isoleucine,
+            'L' => Residue::Leucine,
+            'K' => Residue::Lysine,
+            'M' => Residue::Methionine,
+            'F' => Residue::Phenylalanine,
+            'P' => Residue::Proline,
+            'O' => Residue::Pyrrolysine,
+            'U' => Residue::Selenocysteine,
+            'S' => Residue::Serine,
+            'T' => Residue::Threonine,
+            'W' => Residue::Tryptophan,
+            'Y' => Residue::Tyrosine,
+            'V' => Residue::Valine,
+            'B' => Residue::AsparagineOrAsparticAcid,
+            'Z' => Residue::GlutamineOrGlutamicAcid,
+            'J' => Residue::LeucineOrIsoleucine,
+            'X' => Residue::Unknown,
+            _ => return None,
+        })
+    }
+}
+
 impl Display for Residue {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        match serde_json::to_value(self) {
-            Ok(Value::String(ref s)) => f.pad(s),
-            _ => panic!("Residue didn't serialize to a string"),
-        }
+        f.pad(self.three_letter())
     }
 }
 
@@ -327,6 +1510,29 @@ pub enum DsspStructure {
     Bend,
 }
 
+impl Display for DsspStructure {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.pad(match *self {
+            DsspStructure::None         => " ",
+            DsspStructure::Helix310     => "G",
+            DsspStructure::HelixAlpha   => "H",
+            DsspStructure::HelixPi      => "I",
+            DsspStructure::TurnHBond    => "T",
+            DsspStructure::BetaExtended => "E",
+            DsspStructure::BetaIsolated => "B",
+            DsspStructure::Bend         => "S",
+        })
+    }
+}
+
+impl FromStr for DsspStructure {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(Value::from(s)).map_err(From::from)
+    }
+}
+
 /// An edge in the interaction graph.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Edge {
@@ -385,10 +1591,108 @@ pub struct Edge {
     /// Corrected Mutual Information
     #[serde(rename = "MIcorrected", default, skip_serializing_if = "Option::is_none")]
     pub corrected_mi: Option<f64>,
+    /// Any other fields sent by the server which this crate doesn't model.
+    #[serde(flatten, default, skip_serializing_if = "Map::is_empty")]
+    pub extra: Map<String, Value>,
+}
+
+impl Edge {
+    /// Creates an edge of the given type between two nodes. The atoms have
+    /// empty names, distance and energy are zero, and all optional fields
+    /// (including the angle and the directional roles) are `None`.
+    /// Fields are public, so they can be adjusted afterwards.
+    pub fn new(node_id_1: NodeId, node_id_2: NodeId, interaction: Interaction) -> Self {
+        Edge {
+            node_id_1,
+            node_id_2,
+            interaction,
+            atom_1: Atom::Name(String::new()),
+            atom_2: Atom::Name(String::new()),
+            distance: 0.0,
+            angle: None,
+            energy: 0.0,
+            donor: None,
+            positive: None,
+            cation: None,
+            mutual_inf: None,
+            apc: None,
+            corrected_mi: None,
+            extra: Map::new(),
+        }
+    }
+
+    /// A key identifying the edge by its endpoints and its interaction,
+    /// regardless of its direction, for use in sorted or hashed collections,
+    /// which `Edge` itself can't be in, since it has floating-point fields.
+    /// See `EdgeKey` for details.
+    pub fn key(&self) -> EdgeKey {
+        EdgeKey::from(self)
+    }
+
+    /// Returns `true` if the given node is either endpoint of this edge.
+    pub fn involves(&self, id: &NodeId) -> bool {
+        self.node_id_1 == *id || self.node_id_2 == *id
+    }
+
+    /// Given one endpoint of this edge, returns the other one, regardless of
+    /// the orientation of the edge. Returns `None` if `id` is not an endpoint.
+    /// For a self-loop, the node itself is returned.
+    pub fn other(&self, id: &NodeId) -> Option<&NodeId> {
+        if self.node_id_1 == *id {
+            Some(&self.node_id_2)
+        } else if self.node_id_2 == *id {
+            Some(&self.node_id_1)
+        } else {
+            None
+        }
+    }
+
+    /// What `distance` was measured between, as far as it can be told from
+    /// the atom fields. See `MeasurementBasis` for the caveats.
+    pub fn measured_between(&self) -> MeasurementBasis {
+        match (&self.atom_1, &self.atom_2) {
+            (Atom::Name(a), Atom::Name(b)) => match (a.trim(), b.trim()) {
+                ("CA", "CA") => MeasurementBasis::AlphaCarbons,
+                ("CB", "CB") => MeasurementBasis::BetaCarbons,
+                _ => MeasurementBasis::Atoms,
+            },
+            (Atom::Coords { .. }, Atom::Coords { .. }) => MeasurementBasis::Centers,
+            _ => MeasurementBasis::AtomAndCenter,
+        }
+    }
+
+    /// Checks that the directional fields (`donor`, `positive`, `cation`)
+    /// are consistent with the main interaction type. Each of them must
+    /// be present on, and only on, hydrogen bonds, ionic bonds, and
+    /// pi-cation interactions, respectively.
+    pub fn validate_roles(&self) -> RingResult<()> {
+        let main_type = self.interaction.main_type;
+        let roles = [
+            ("donor", &self.donor, InteractionMainType::HydrogenBond),
+            ("positive", &self.positive, InteractionMainType::Ionic),
+            ("cation", &self.cation, InteractionMainType::PiCation),
+        ];
+
+        for &(name, role, role_type) in &roles {
+            match (role.is_some(), main_type == role_type) {
+                (true, false) => return Err(Error::Validation(format!(
+                    "edge {} - {}: `{}` is not allowed on a {} interaction",
+                    self.node_id_1, self.node_id_2, name, main_type
+                ))),
+                (false, true) => return Err(Error::Validation(format!(
+                    "edge {} - {}: `{}` is required on a {} interaction",
+                    self.node_id_1, self.node_id_2, name, main_type
+                ))),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// Descriptor of an Interaction Type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Interaction {
     /// Main interaction type.
     pub main_type: InteractionMainType,
@@ -398,9 +1702,67 @@ pub struct Interaction {
     pub subtype_2: InteractionSubType,
 }
 
+impl Interaction {
+    /// Whether this is a hydrogen bond.
+    pub fn is_hydrogen_bond(&self) -> bool {
+        self.main_type == InteractionMainType::HydrogenBond
+    }
+
+    /// Whether this is a van der Waals interaction.
+    pub fn is_vdw(&self) -> bool {
+        self.main_type == InteractionMainType::VanDerWaals
+    }
+
+    /// Whether this is a disulphide bond.
+    pub fn is_disulphide(&self) -> bool {
+        self.main_type == InteractionMainType::Disulphide
+    }
+
+    /// Whether this is an ionic bond.
+    pub fn is_ionic(&self) -> bool {
+        self.main_type == InteractionMainType::Ionic
+    }
+
+    /// Whether this is a pi-pi stacking interaction.
+    pub fn is_pi_pi(&self) -> bool {
+        self.main_type == InteractionMainType::PiPiStack
+    }
+
+    /// Whether this is a pi-cation interaction.
+    pub fn is_pi_cation(&self) -> bool {
+        self.main_type == InteractionMainType::PiCation
+    }
+
+    /// Whether either node interacts via its side chain.
+    pub fn involves_side_chain(&self) -> bool {
+        self.involves(InteractionSubType::SideChain)
+    }
+
+    /// Whether either node interacts via its main chain.
+    pub fn involves_main_chain(&self) -> bool {
+        self.involves(InteractionSubType::MainChain)
+    }
+
+    /// Whether either node interacts via a ligand.
+    pub fn involves_ligand(&self) -> bool {
+        self.involves(InteractionSubType::Ligand)
+    }
+
+    /// Whether either subtype is the given one.
+    fn involves(&self, subtype: InteractionSubType) -> bool {
+        self.subtype_1 == subtype || self.subtype_2 == subtype
+    }
+}
+
 impl Display for Interaction {
+    /// Writes the canonical `main:sub1_sub2` form piece by piece,
+    /// since this is on the hot path of exporting large networks.
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        write!(f, "{}:{}_{}", self.main_type, self.subtype_1, self.subtype_2)
+        f.write_str(self.main_type.as_str())?;
+        f.write_str(":")?;
+        f.write_str(self.subtype_1.as_str())?;
+        f.write_str("_")?;
+        f.write_str(self.subtype_2.as_str())
     }
 }
 
@@ -412,13 +1774,13 @@ impl FromStr for Interaction {
 
         if parts.len() == 2 {
             let main = parts[0];
-            let parts: Vec<_> = parts[1].split('_').collect();
+            let subtypes: Vec<_> = parts[1].split('_').collect();
 
-            if parts.len() == 2 {
+            if subtypes.len() == 2 {
                 Ok(Interaction {
                     main_type: main.parse()?,
-                    subtype_1: parts[0].parse()?,
-                    subtype_2: parts[1].parse()?,
+                    subtype_1: subtypes[0].parse()?,
+                    subtype_2: subtypes[1].parse()?,
                 })
             } else {
                 Err(Error::Serialization(String::from(
@@ -462,7 +1824,7 @@ impl<'a> Visitor<'a> for InteractionVisitor {
 }
 
 /// The set of possible main interaction types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum InteractionMainType {
     /// Hydrogen bond.
     #[serde(rename = "HBOND")]
@@ -484,12 +1846,36 @@ pub enum InteractionMainType {
     PiCation,
 }
 
+impl InteractionMainType {
+    /// The name of the main interaction type as used by RING, e.g. `HBOND`.
+    /// This is also how it is serialized and displayed.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            InteractionMainType::HydrogenBond => "HBOND",
+            InteractionMainType::VanDerWaals  => "VDW",
+            InteractionMainType::Disulphide   => "SSBOND",
+            InteractionMainType::Ionic        => "IONIC",
+            InteractionMainType::PiPiStack    => "PIPISTACK",
+            InteractionMainType::PiCation     => "PICATION",
+        }
+    }
+
+    /// All main interaction types, in declaration order.
+    pub fn all() -> &'static [InteractionMainType] {
+        &[
+            InteractionMainType::HydrogenBond,
+            InteractionMainType::VanDerWaals,
+            InteractionMainType::Disulphide,
+            InteractionMainType::Ionic,
+            InteractionMainType::PiPiStack,
+            InteractionMainType::PiCation,
+        ]
+    }
+}
+
 impl Display for InteractionMainType {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        match serde_json::to_value(self) {
-            Ok(Value::String(ref s)) => f.pad(s),
-            _ => panic!("InteractionMainType didn't serialize to a string"),
-        }
+        f.pad(self.as_str())
     }
 }
 
@@ -503,10 +1889,7 @@ impl FromStr for InteractionMainType {
 
 impl Display for InteractionSubType {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        match serde_json::to_value(self) {
-            Ok(Value::String(ref s)) => f.pad(s),
-            _ => panic!("InteractionSubType didn't serialize to a string"),
-        }
+        f.pad(self.as_str())
     }
 }
 
@@ -519,7 +1902,7 @@ impl FromStr for InteractionSubType {
 }
 
 /// The set of possible interaction subtypes.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum InteractionSubType {
     /// Interaction on the main chain.
     #[serde(rename = "MC")]
@@ -532,7 +1915,52 @@ pub enum InteractionSubType {
     Ligand,
 }
 
+impl InteractionSubType {
+    /// The name of the interaction subtype as used by RING, e.g. `MC`.
+    /// This is also how it is serialized and displayed.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            InteractionSubType::MainChain => "MC",
+            InteractionSubType::SideChain => "SC",
+            InteractionSubType::Ligand    => "LIG",
+        }
+    }
+
+    /// All interaction subtypes, in declaration order.
+    pub fn all() -> &'static [InteractionSubType] {
+        &[
+            InteractionSubType::MainChain,
+            InteractionSubType::SideChain,
+            InteractionSubType::Ligand,
+        ]
+    }
+}
+
+/// What the `distance` of an edge was measured between. Returned by
+/// `Edge::measured_between()`.
+///
+/// This is inferred from the atoms of the edge alone, so it can't always
+/// tell the network policies apart: e.g. with `NetworkPolicy::Closest`,
+/// an edge whose closest atoms happen to be the two alpha carbons is
+/// reported as `AlphaCarbons`, just like edges of `NetworkPolicy::CAlpha`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MeasurementBasis {
+    /// Between two named atoms, e.g. the closest ones.
+    Atoms,
+    /// Between the two alpha carbons (`CA`).
+    AlphaCarbons,
+    /// Between the two beta carbons (`CB`).
+    BetaCarbons,
+    /// Between two points which are not atoms, e.g. centers of mass
+    /// (`NetworkPolicy::Lollipop`) or barycenters of aromatic rings.
+    Centers,
+    /// Between a named atom and a center, e.g. a cation and the
+    /// barycenter of an aromatic ring.
+    AtomAndCenter,
+}
+
 /// Describes an atom either by its name or by its coordinates.
+/// See the `FromStr` impl for how the two are told apart.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Atom {
     /// A named atom.
@@ -560,15 +1988,19 @@ impl Display for Atom {
 impl FromStr for Atom {
     type Err = Error;
 
+    /// A string is parsed as `Coords` if and only if it consists of
+    /// exactly three comma-separated parts, each of which is a valid `f64`
+    /// (surrounding whitespace is allowed). Anything else, including e.g.
+    /// `1.0,2.0,x` or `1,2,3,4`, is parsed as a `Name`, so this never fails.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<_> = s.split(',').collect();
-
-        if parts.len() == 3 {
-            Ok(Atom::Coords {
-                x: parts[0].parse()?,
-                y: parts[1].parse()?,
-                z: parts[2].parse()?,
-            })
+        let coords: Vec<f64> = s
+            .split(',')
+            .map(|part| part.trim().parse())
+            .collect::<Result<_, _>>()
+            .unwrap_or_default();
+
+        if let [x, y, z] = coords[..] {
+            Ok(Atom::Coords { x, y, z })
         } else {
             Ok(Atom::Name(s.into()))
         }
@@ -604,6 +2036,28 @@ impl<'a> Visitor<'a> for AtomVisitor {
     }
 }
 
+/// The value RING sends in place of an angle which couldn't be computed.
+pub const INVALID_ANGLE: f64 = -999.9;
+
+/// Converts an angle as sent by RING into `None` if it is `INVALID_ANGLE`.
+///
+/// Values within 0.001 of `INVALID_ANGLE` are also treated as invalid, so
+/// that the sentinel is recognized even after a lossy conversion, e.g. to
+/// `f32` and back. Real angles are never anywhere near -999.9 degrees.
+pub fn angle_from_raw(raw: f64) -> Option<f64> {
+    if (raw - INVALID_ANGLE).abs() < 1e-3 {
+        None
+    } else {
+        Some(raw)
+    }
+}
+
+/// Converts an angle into the representation used by RING, i.e. `None`
+/// into `INVALID_ANGLE`.
+pub fn angle_to_raw(angle: Option<f64>) -> f64 {
+    angle.unwrap_or(INVALID_ANGLE)
+}
+
 /// De/Serialize an invalid angle of -999.9 as `None`.
 mod serde_angle {
     use serde::{
@@ -611,10 +2065,11 @@ mod serde_angle {
         de::{ Deserializer, Visitor, Error },
     };
     use std::fmt::{ Formatter, Result as FmtResult };
+    use super::{ angle_from_raw, angle_to_raw };
 
     /// Serialize a `None` angle as the invalid value -999.9.
     pub fn serialize<S: Serializer>(value: &Option<f64>, s: S) -> Result<S::Ok, S::Error> {
-        s.serialize_f64(value.unwrap_or(-999.9))
+        s.serialize_f64(angle_to_raw(*value))
     }
 
     /// Deserialize an invalid angle of -999.9 as `None`.
@@ -633,13 +2088,8 @@ mod serde_angle {
             f.write_str("a number representing an angle")
         }
 
-        #[allow(clippy::float_cmp)]
         fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
-            if v == -999.9 {
-                Ok(None)
-            } else {
-                Ok(Some(v))
-            }
+            Ok(angle_from_raw(v))
         }
 
         #[allow(clippy::cast_precision_loss)]
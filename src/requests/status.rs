@@ -1,10 +1,18 @@
 //! Endpoint for querying the status of a job.
 
 use std::borrow::Cow;
+use std::result::Result as StdResult;
+use serde::{
+    ser::{ Serialize, Serializer },
+    de::{ Deserialize, Deserializer },
+};
+use serde_json::Value;
+use reqwest::{ Method, Response };
 use super::Request;
 use crate::{
     settings::Settings,
     job::{ JobId, JobStatus },
+    error::Result,
 };
 
 /// A status request.
@@ -28,16 +36,139 @@ pub struct StatusResponse {
     /// If a file name has been supplied upon submission, it is captured here.
     #[serde(default, rename = "fileName", skip_serializing_if = "Option::is_none")]
     pub file_name: Option<String>,
+    /// The version of RING that processed the job, if the server reports it.
+    #[serde(default, rename = "version", alias = "ringVersion", skip_serializing_if = "Option::is_none")]
+    pub ring_version: Option<String>,
+    /// The error message reported by the server, typically explaining
+    /// why the job failed (e.g. a bad chain or an unparseable file).
+    /// Servers report it as `error`, `message` or `errorMessage`; if more
+    /// than one of these is present, the first one in this order wins.
+    #[serde(flatten, serialize_with = "serialize_error_message",
+            deserialize_with = "deserialize_error_message")]
+    pub error_message: Option<String>,
+    /// The progress of the job between 0 and 1, if the server reports it,
+    /// e.g. while it is `Partial`. Percentages (strings like `"45%"`) are
+    /// normalized into this range; unrecognized values are ignored.
+    #[serde(default, deserialize_with = "deserialize_progress",
+            skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f32>,
     /// The job settings are echoed back.
     #[serde(flatten, default)]
     pub settings: Settings,
 }
 
+impl StatusResponse {
+    /// The reason the job failed, if it did fail and the server said why.
+    pub fn failure_reason(&self) -> Option<&str> {
+        if self.status.is_failed() {
+            self.error_message.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+/// The keys under which servers may report an error message.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ErrorMessageKeys {
+    /// The canonical key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// An alternative key used by some servers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    /// An alternative key used by some servers.
+    #[serde(default, rename = "errorMessage", skip_serializing_if = "Option::is_none")]
+    error_message: Option<String>,
+}
+
+/// Serializes an error message under the canonical `error` key.
+fn serialize_error_message<S>(message: &Option<String>, serializer: S) -> StdResult<S::Ok, S::Error>
+    where S: Serializer
+{
+    ErrorMessageKeys { error: message.clone(), ..ErrorMessageKeys::default() }.serialize(serializer)
+}
+
+/// Deserializes the first error message found among the known keys.
+fn deserialize_error_message<'de, D>(deserializer: D) -> StdResult<Option<String>, D::Error>
+    where D: Deserializer<'de>
+{
+    let keys = ErrorMessageKeys::deserialize(deserializer)?;
+    Ok(keys.error.or(keys.message).or(keys.error_message))
+}
+
+/// Deserializes a progress indicator. Numbers and numeric strings are
+/// fractions between 0 and 1, and only strings ending in `%` are taken to
+/// be percentages. Anything else (free-form text, out-of-range numbers,
+/// etc.) means that the progress is unknown, and yields `None`.
+fn deserialize_progress<'de, D>(deserializer: D) -> StdResult<Option<f32>, D::Error>
+    where D: Deserializer<'de>
+{
+    let fraction = match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(ref s) => match s.trim().strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f64>().ok().map(|p| p / 100.0),
+            None => s.trim().parse().ok(),
+        },
+        _ => None,
+    };
+
+    #[allow(clippy::cast_possible_truncation)]
+    Ok(fraction.filter(|f| (0.0..=1.0).contains(f)).map(|f| f as f32))
+}
+
 impl Request for Status {
     type Body = ();
     type Response = StatusResponse;
 
-    fn endpoint(&self) -> Cow<str> {
-        format!("/status/{}", self.job_id).into()
+    fn endpoint(&self) -> Cow<'_, str> {
+        self.job_id.endpoint("status").into()
+    }
+}
+
+/// A request for the status of all jobs visible to the client.
+///
+/// **NOTE:** the public RING service doesn't document a job listing
+/// endpoint, so this may only work with servers or mirrors which provide
+/// one, possibly requiring authentication. Otherwise, sending it results
+/// in an `Error::Http` (typically `404 Not Found`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ListJobs;
+
+impl Request for ListJobs {
+    type Body = ();
+    type Response = Vec<StatusResponse>;
+
+    fn endpoint(&self) -> Cow<'_, str> {
+        Cow::from("/jobs")
+    }
+}
+
+/// A request for cancelling a job, e.g. a long-running MSA, in order to
+/// free server resources. The response carries no information; if the
+/// request succeeds, the job has been cancelled.
+///
+/// **NOTE:** the public RING service doesn't document a cancellation
+/// endpoint, so, just like `ListJobs`, this may only work with servers
+/// which provide one. Otherwise, sending it results in an `Error::Http`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CancelJob {
+    /// The ID of the job to be cancelled.
+    pub job_id: JobId,
+}
+
+impl Request for CancelJob {
+    type Body = ();
+    type Response = ();
+
+    const METHOD: Method = Method::DELETE;
+
+    fn endpoint(&self) -> Cow<'_, str> {
+        self.job_id.endpoint("jobs").into()
+    }
+
+    /// The body, if any, is ignored.
+    fn decode(_: Response) -> Result<Self::Response> {
+        Ok(())
     }
 }
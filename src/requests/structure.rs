@@ -0,0 +1,30 @@
+//! Downloading the structure file a job was computed from.
+
+use std::borrow::Cow;
+use reqwest::Response;
+use super::Request;
+use crate::{
+    job::JobId,
+    error::Result,
+};
+
+/// A request for the original PDB/structure file of a job, as used by RING.
+/// The response is the plain contents of the file, not JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DownloadStructure {
+    /// The ID of the job whose structure file is requested.
+    pub job_id: JobId,
+}
+
+impl Request for DownloadStructure {
+    type Body = ();
+    type Response = String;
+
+    fn endpoint(&self) -> Cow<'_, str> {
+        self.job_id.endpoint("structure").into()
+    }
+
+    fn decode(mut response: Response) -> Result<Self::Response> {
+        response.text().map_err(From::from)
+    }
+}
@@ -1,17 +1,21 @@
 //! Submit a job.
 
 use std::borrow::Cow;
-use std::io::Read;
-use std::path::Path;
+use std::io::{ Read, Error as IoError };
+use std::path::{ Path, PathBuf };
 use std::ffi::OsStr;
 use std::fs::read_to_string;
+#[cfg(feature = "gzip-files")]
+use std::fs::File;
+#[cfg(feature = "gzip-files")]
+use flate2::read::GzDecoder;
 use reqwest::Method;
 use super::{ Request, RequestBody };
 use crate::{
     settings::Settings,
     multipart::FormFile,
     job::{ JobId, JobStatus },
-    error::Result,
+    error::{ Error, Result },
 };
 
 /// Submitting a RING job based on a known PDB ID.
@@ -25,6 +29,22 @@ pub struct SubmitId {
     pub settings: Settings,
 }
 
+/// Submitting a RING job based on a known PDB ID, like `SubmitId`, but
+/// with a `GET` request carrying the PDB ID and the settings in the URL
+/// query string instead of a JSON body.
+///
+/// **NOTE:** this is only useful for deployments which accept submissions
+/// in this form, e.g. because their handling of JSON bodies is unreliable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubmitIdQuery {
+    /// The PDB ID of the protein to be RING'd.
+    #[serde(rename = "pdbName")]
+    pub pdb_id: String,
+    /// The settings with which to perform the job.
+    #[serde(flatten, default)]
+    pub settings: Settings,
+}
+
 /// Submitting a request based on a PDB structure.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SubmitStructure {
@@ -39,14 +59,49 @@ pub struct SubmitStructure {
     pub settings: Settings,
 }
 
+/// The file format of a submitted structure. The RING server infers it
+/// from the extension of the uploaded file's name. The default is `Pdb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StructureFormat {
+    /// Legacy PDB format.
+    Pdb,
+    /// PDBx/mmCIF format.
+    Cif,
+}
+
+impl StructureFormat {
+    /// The file name extension of this format, without the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            StructureFormat::Pdb => "pdb",
+            StructureFormat::Cif => "cif",
+        }
+    }
+
+    /// The file name used in the form when no real file name is known.
+    fn dummy_file_name(&self) -> String {
+        format!("rust_ring_api_dummy.{}", self.extension())
+    }
+}
+
+impl Default for StructureFormat {
+    fn default() -> Self {
+        StructureFormat::Pdb
+    }
+}
+
 /// The response from the "submit" endpoint.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SubmitResponse {
     /// The Job ID which can be used later for querying the results.
     #[serde(rename = "jobid")]
     pub job_id: JobId,
     /// The initially-reported status of the job, usually "in progress".
     pub status: JobStatus,
+    /// The job settings, as interpreted by the server, if it echoes them.
+    /// Settings which the server doesn't echo have their default values.
+    #[serde(flatten, default)]
+    pub settings: Settings,
 }
 
 impl SubmitId {
@@ -60,16 +115,97 @@ impl SubmitId {
     }
 }
 
+impl SubmitId {
+    /// Convenience constructor.
+    /// Like `with_pdb_id()`, but checks that the ID has the shape of a PDB
+    /// ID, and converts it to uppercase. Accepted forms are the legacy one,
+    /// i.e. 4 alphanumeric characters starting with a non-zero digit (e.g.
+    /// `3S6A`), and the extended one, i.e. `pdb_` followed by 8 alphanumeric
+    /// characters (e.g. `pdb_00003s6a`). Returns `Error::Serialization` if
+    /// the ID has neither form.
+    pub fn with_validated_pdb_id<T: AsRef<str>>(pdb_id: T) -> Result<Self> {
+        let id = pdb_id.as_ref().trim();
+        let alphanumeric = |s: &str| s.chars().all(|c| c.is_ascii_alphanumeric());
+        let extended = id.len() == 12 && id.is_char_boundary(4) && {
+            let (prefix, code) = id.split_at(4);
+            prefix.eq_ignore_ascii_case("pdb_") && alphanumeric(code)
+        };
+        let legacy = id.len() == 4
+            && alphanumeric(id)
+            && matches!(id.as_bytes()[0], b'1'..=b'9');
+
+        if legacy || extended {
+            Ok(Self::with_pdb_id(id.to_ascii_uppercase()))
+        } else {
+            Err(Error::Serialization(format!(
+                "`{}` is not a PDB ID; expected e.g. `3S6A` or `pdb_00003s6a`",
+                id.escape_debug()
+            )))
+        }
+    }
+}
+
+impl SubmitIdQuery {
+    /// Convenience constructor.
+    /// Creates a query-string submit request with the default settings.
+    pub fn with_pdb_id<T: Into<String>>(pdb_id: T) -> Self {
+        SubmitIdQuery {
+            pdb_id: pdb_id.into(),
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl From<SubmitId> for SubmitIdQuery {
+    fn from(request: SubmitId) -> Self {
+        SubmitIdQuery {
+            pdb_id: request.pdb_id,
+            settings: request.settings,
+        }
+    }
+}
+
 impl SubmitStructure {
     /// Convenience constructor.
     /// Creates a submit structure request with the specified structure,
     /// default settings, and no file name. (Only for the form's
     /// Content-Disposition, a dummy file name will be used.)
     pub fn with_pdb_structure<T: Into<String>>(pdb_structure: T) -> Self {
+        Self::with_structure(pdb_structure, StructureFormat::Pdb)
+    }
+
+    /// Convenience constructor.
+    /// Like `with_pdb_structure()`, but the dummy file name has the
+    /// extension of the given format, so that e.g. mmCIF can be submitted.
+    pub fn with_structure<T: Into<String>>(structure: T, format: StructureFormat) -> Self {
+        Self::with_structure_and_file_name(structure.into(), None, format)
+    }
+
+    /// Convenience constructor.
+    /// Creates a submit structure request from raw bytes, with the default
+    /// settings and the specified file name. The bytes are copied and sent
+    /// verbatim, so they don't need to be valid UTF-8. Useful for e.g.
+    /// memory-mapped files.
+    pub fn with_bytes_slice<T: Into<String>>(bytes: &[u8], file_name: T) -> Self {
+        let name = file_name.into();
+
+        SubmitStructure {
+            pdb_structure: FormFile::with_contents_and_file_name(bytes, name.clone()),
+            file_name: Some(name),
+            settings: Settings::default(),
+        }
+    }
+
+    /// Convenience constructor.
+    /// Creates a submit structure request from raw bytes, with the default
+    /// settings and no file name. (Only for the form's Content-Disposition,
+    /// a dummy file name will be used.) The bytes are sent verbatim, not
+    /// sanitized, so stray non-UTF-8 bytes, e.g. in remarks, are fine.
+    pub fn with_bytes(bytes: Vec<u8>) -> Self {
         SubmitStructure {
             pdb_structure: FormFile::with_contents_and_file_name(
-                pdb_structure.into(),
-                String::from("rust_ring_api_dummy.pdb"),
+                bytes,
+                StructureFormat::Pdb.dummy_file_name(),
             ),
             file_name: None,
             settings: Settings::default(),
@@ -79,7 +215,9 @@ impl SubmitStructure {
     /// Convenience constructor.
     /// Creates a submit structure request from an `io::Read`, with the
     /// default settings and no file name. (Only for the form's
-    /// Content-Disposition, a dummy file name will be used.)
+    /// Content-Disposition, a dummy file name will be used.) The contents
+    /// must be valid UTF-8; otherwise, read them into a buffer and use
+    /// `with_bytes()` instead.
     pub fn with_reader<R: Read>(mut reader: R) -> Result<Self> {
         let mut structure = String::new();
         reader.read_to_string(&mut structure)?;
@@ -90,32 +228,103 @@ impl SubmitStructure {
     /// Creates a submit structure request from a file, with the
     /// default settings and the specified file name (if the latter can be
     /// converted to a UTF-8 string - otherwise, a dummy one will be used).
+    /// If the file can't be read, the error (`Error::File`) includes its path.
     pub fn with_pdb_file<P: AsRef<Path>>(file: P) -> Result<Self> {
         let path = file.as_ref();
         let maybe_file_name = path
             .file_name()
             .and_then(OsStr::to_str)
             .map(Into::into);
-        let structure = read_to_string(path)?;
+        let structure = read_to_string(path).map_err(|cause| file_error(path, cause))?;
+
+        Ok(Self::with_structure_and_file_name(structure, maybe_file_name, StructureFormat::Pdb))
+    }
+
+    /// Convenience constructor.
+    /// Creates a submit structure request which streams the given file
+    /// when it is sent, instead of reading it into memory upfront. This
+    /// keeps memory usage low for large structures. The file name is
+    /// derived as in `with_pdb_file()`; however, since the file is only
+    /// opened when the request is sent, errors only surface then.
+    pub fn with_streamed_file<P: Into<PathBuf>>(file: P) -> Self {
+        let path = file.into();
+        let maybe_file_name: Option<String> = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map(Into::into);
 
-        Ok(SubmitStructure {
+        SubmitStructure {
             pdb_structure: FormFile::with_contents_and_file_name(
-                structure,
+                path,
                 maybe_file_name.clone().unwrap_or_else(
-                    || String::from("rust_ring_api_dummy.pdb")
-                )
+                    || StructureFormat::Pdb.dummy_file_name()
+                ),
+            ),
+            file_name: maybe_file_name,
+            settings: Settings::default(),
+        }
+    }
+
+    /// Convenience constructor.
+    /// Creates a submit structure request from an mmCIF file. Works like
+    /// `with_pdb_file()`, except that the dummy file name, used when the
+    /// real one is not valid UTF-8, has a `.cif` extension.
+    pub fn with_cif_file<P: AsRef<Path>>(file: P) -> Result<Self> {
+        let path = file.as_ref();
+        let maybe_file_name = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map(Into::into);
+        let structure = read_to_string(path).map_err(|cause| file_error(path, cause))?;
+
+        Ok(Self::with_structure_and_file_name(structure, maybe_file_name, StructureFormat::Cif))
+    }
+
+    /// Convenience constructor.
+    /// Creates a submit structure request from a gzip-compressed file, e.g.
+    /// `1abc.pdb.gz`. It is decompressed, then treated exactly like in the
+    /// case of `with_pdb_file()`, except that the `.gz` extension, if any,
+    /// is removed from the file name.
+    #[cfg(feature = "gzip-files")]
+    pub fn with_gzip_pdb_file<P: AsRef<Path>>(file: P) -> Result<Self> {
+        let path = file.as_ref();
+        let maybe_file_name = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map(|name| name.trim_end_matches(".gz").into());
+        let mut structure = String::new();
+
+        File::open(path)
+            .and_then(|compressed| GzDecoder::new(compressed).read_to_string(&mut structure))
+            .map_err(|cause| file_error(path, cause))?;
+
+        Ok(Self::with_structure_and_file_name(structure, maybe_file_name, StructureFormat::Pdb))
+    }
+
+    /// Private helper for the string-based constructors. Uses a dummy
+    /// file name of the given format for the form if `maybe_file_name`
+    /// is `None`.
+    fn with_structure_and_file_name(
+        structure: String,
+        maybe_file_name: Option<String>,
+        format: StructureFormat,
+    ) -> Self {
+        SubmitStructure {
+            pdb_structure: FormFile::with_contents_and_file_name(
+                structure,
+                maybe_file_name.clone().unwrap_or_else(|| format.dummy_file_name()),
             ),
             file_name: maybe_file_name,
             settings: Settings::default(),
-        })
+        }
     }
 
     /// Builder method for unconditionally setting the file name.
     pub fn file_name<T: Into<String>>(mut self, file_name: T) -> Self {
-        let file_name = file_name.into();
+        let name = file_name.into();
 
-        self.file_name.replace(file_name.clone());
-        self.pdb_structure.set_file_name(file_name);
+        self.file_name.replace(name.clone());
+        self.pdb_structure.set_file_name(name);
         self
     }
 
@@ -134,13 +343,25 @@ impl SubmitStructure {
     }
 }
 
+impl<'a> From<&'a Path> for SubmitStructure {
+    /// Equivalent to `with_streamed_file()`, so that it can't fail.
+    fn from(path: &'a Path) -> Self {
+        Self::with_streamed_file(path)
+    }
+}
+
+/// Attaches the path of the file being accessed to an I/O error.
+fn file_error(path: &Path, cause: IoError) -> Error {
+    Error::File { path: path.into(), cause }
+}
+
 impl Request for SubmitId {
     type Body = Self;
     type Response = SubmitResponse;
 
     const METHOD: Method = Method::POST;
 
-    fn endpoint(&self) -> Cow<str> {
+    fn endpoint(&self) -> Cow<'_, str> {
         Cow::from("/submit")
     }
 
@@ -149,13 +370,31 @@ impl Request for SubmitId {
     }
 }
 
+impl Request for SubmitIdQuery {
+    type Body = Self;
+    type Response = SubmitResponse;
+
+    /// Each request creates a new job, even though it is a `GET`.
+    fn is_idempotent() -> bool {
+        false
+    }
+
+    fn endpoint(&self) -> Cow<'_, str> {
+        Cow::from("/submit")
+    }
+
+    fn body(&self) -> RequestBody<&Self::Body> {
+        RequestBody::Query(self)
+    }
+}
+
 impl Request for SubmitStructure {
     type Body = Self;
     type Response = SubmitResponse;
 
     const METHOD: Method = Method::POST;
 
-    fn endpoint(&self) -> Cow<str> {
+    fn endpoint(&self) -> Cow<'_, str> {
         Cow::from("/submit")
     }
 
@@ -0,0 +1,25 @@
+//! Querying the version of the RING server.
+
+use std::borrow::Cow;
+use super::Request;
+
+/// A request for the version of the RING webservice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ServerVersion;
+
+/// Response to a version request.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ServerVersionResponse {
+    /// The version of RING running on the server, if it reports one.
+    #[serde(default, rename = "version", alias = "ringVersion", skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl Request for ServerVersion {
+    type Body = ();
+    type Response = ServerVersionResponse;
+
+    fn endpoint(&self) -> Cow<'_, str> {
+        Cow::from("/version")
+    }
+}
@@ -4,8 +4,9 @@ use std::str::FromStr;
 use std::fmt::{ Display, Formatter, Result as FmtResult };
 use serde::{
     ser::{ Serialize, Serializer, SerializeMap, Error },
-    de::{ Deserialize, Deserializer, Visitor, MapAccess },
+    de::{ Deserialize, Deserializer, Visitor, MapAccess, IgnoredAny, Unexpected },
 };
+use crate::error::Error as RingError;
 
 /// Parameters for submitting a job.
 /// See the [documentation](http://protein.bio.unipd.it/ring/help#params)
@@ -31,6 +32,13 @@ pub struct Settings {
     /// Calculate mutual information from a multiple alignment via BLAST (slow!)
     /// Default `false`.
     pub perform_msa: bool,
+    /// Run in RING-MD mode, for analysing molecular dynamics trajectories.
+    /// Default `false`.
+    pub ring_md: bool,
+    /// Which model (1-based) of a multi-model structure, e.g. an NMR
+    /// ensemble, to compute. Default `None`, i.e. the server's default,
+    /// which is usually the first model.
+    pub model: Option<usize>,
 }
 
 /// Chain ID for computing a single chain or "all" for computing all chains.
@@ -39,8 +47,128 @@ pub struct Settings {
 pub enum Chain {
     /// Compute all chains.
     All,
-    /// Compute a single chain with the given character ID.
-    Id(char),
+    /// Compute a single chain with the given ID.
+    Id(ChainId),
+}
+
+impl FromStr for Chain {
+    type Err = RingError;
+
+    /// Parses the spellings of "all chains" seen in server responses,
+    /// i.e. `""`, `"*"`, and `"all"` in any case, as `Chain::All`, and
+    /// anything else as a chain ID. This makes a chain with the ID `ALL`
+    /// unrepresentable, but such chains don't occur in practice.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s == "*" || s.eq_ignore_ascii_case("all") {
+            Ok(Chain::All)
+        } else {
+            s.parse().map(Chain::Id)
+        }
+    }
+}
+
+impl Display for Chain {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        match *self {
+            Chain::All => formatter.pad("all"),
+            Chain::Id(ref id) => id.fmt(formatter),
+        }
+    }
+}
+
+/// The ID of a chain in a PDB or mmCIF structure, e.g. `A` or `BC`.
+///
+/// Stored inline (so it is `Copy`), and consists of between 1 and
+/// `ChainId::MAX_LEN` printable ASCII characters other than `:`, which
+/// separates the components of node IDs, and other than the wildcard `*`,
+/// which stands for all chains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChainId([u8; ChainId::MAX_LEN]);
+
+impl ChainId {
+    /// The maximal number of characters in a chain ID.
+    pub const MAX_LEN: usize = 4;
+
+    /// Returns the string representation of the chain ID.
+    pub fn as_str(&self) -> &str {
+        let len = self.0.iter().position(|&b| b == 0).unwrap_or(Self::MAX_LEN);
+        std::str::from_utf8(&self.0[..len]).expect("chain ID is not ASCII")
+    }
+}
+
+impl FromStr for ChainId {
+    type Err = RingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(RingError::Serialization(String::from(
+                "empty string is not a valid chain ID"
+            )));
+        }
+
+        if s.len() > Self::MAX_LEN {
+            return Err(RingError::Serialization(format!(
+                "chain ID `{}` is longer than {} characters", s, Self::MAX_LEN
+            )));
+        }
+
+        if !s.bytes().all(|b| b.is_ascii_graphic() && b != b':' && b != b'*') {
+            return Err(RingError::Serialization(format!(
+                "chain ID `{}` contains invalid characters", s
+            )));
+        }
+
+        let mut bytes = [0; Self::MAX_LEN];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+
+        Ok(ChainId(bytes))
+    }
+}
+
+impl Display for ChainId {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        formatter.pad(self.as_str())
+    }
+}
+
+impl AsRef<str> for ChainId {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Serialize for ChainId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ChainId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        /// Just because we are forced to use a visitor.
+        #[derive(Debug, Clone, Copy)]
+        struct ChainIdVisitor;
+
+        impl<'de> Visitor<'de> for ChainIdVisitor {
+            type Value = ChainId;
+
+            fn expecting(&self, formatter: &mut Formatter) -> FmtResult {
+                formatter.pad("a chain ID of at most 4 characters")
+            }
+
+            fn visit_char<E: Error>(self, v: char) -> Result<Self::Value, E> {
+                self.visit_str(v.encode_utf8(&mut [0; 4]))
+            }
+
+            fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(ChainIdVisitor)
+    }
 }
 
 /// Which atoms to consider when computing interactions.
@@ -61,8 +189,35 @@ pub enum NetworkPolicy {
     CBeta,
 }
 
+impl NetworkPolicy {
+    /// The name of the policy as used by RING, e.g. `ca`.
+    /// This is also how it is serialized, displayed and parsed.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            NetworkPolicy::Closest  => "closest",
+            NetworkPolicy::Lollipop => "lollipop",
+            NetworkPolicy::CAlpha   => "ca",
+            NetworkPolicy::CBeta    => "cb",
+        }
+    }
+
+    /// All network policies, in declaration order.
+    pub fn all() -> &'static [NetworkPolicy] {
+        &[
+            NetworkPolicy::Closest,
+            NetworkPolicy::Lollipop,
+            NetworkPolicy::CAlpha,
+            NetworkPolicy::CBeta,
+        ]
+    }
+}
+
 /// Which interaction(s) to return for each edge (pair of interacting residues).
 /// The default is `Multiple`.
+///
+/// When deserializing `Settings` which have several of the corresponding
+/// flags set, the most restrictive one wins: `NoSpecific`, then
+/// `MostEnergetic`, then `All`. Flags set to `"false"` are ignored.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum InteractionType {
     /// Return all interactions found.
@@ -75,6 +230,70 @@ pub enum InteractionType {
     NoSpecific,
 }
 
+impl InteractionType {
+    /// The canonical name of the interaction type, e.g. `most_energetic`,
+    /// used for displaying and parsing. (In `Settings`, interaction types
+    /// are serialized as flags instead.)
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            InteractionType::All           => "all",
+            InteractionType::Multiple      => "multiple",
+            InteractionType::MostEnergetic => "most_energetic",
+            InteractionType::NoSpecific    => "no_specific",
+        }
+    }
+
+    /// All interaction types, in declaration order.
+    pub fn all() -> &'static [InteractionType] {
+        &[
+            InteractionType::All,
+            InteractionType::Multiple,
+            InteractionType::MostEnergetic,
+            InteractionType::NoSpecific,
+        ]
+    }
+}
+
+impl Display for NetworkPolicy {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for NetworkPolicy {
+    type Err = RingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .iter()
+            .cloned()
+            .find(|policy| policy.as_str() == s)
+            .ok_or_else(|| RingError::Serialization(format!(
+                "unknown network policy `{}`; expected closest, lollipop, ca or cb", s
+            )))
+    }
+}
+
+impl Display for InteractionType {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for InteractionType {
+    type Err = RingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .iter()
+            .cloned()
+            .find(|interactions| interactions.as_str() == s)
+            .ok_or_else(|| RingError::Serialization(format!(
+                "unknown interaction type `{}`; expected all, multiple, most_energetic or no_specific", s
+            )))
+    }
+}
+
 /// Distance thresholds (maximum) between atoms.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
@@ -99,7 +318,120 @@ pub struct Thresholds {
     pub disulphide: f32,
 }
 
+/// Identifies one of the fields of `Thresholds`, e.g. for parameter sweeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThresholdField {
+    /// `Thresholds::hydrogen`.
+    Hydrogen,
+    /// `Thresholds::van_der_waals`.
+    VanDerWaals,
+    /// `Thresholds::ionic`.
+    Ionic,
+    /// `Thresholds::pi_pi`.
+    PiPi,
+    /// `Thresholds::pi_cation`.
+    PiCation,
+    /// `Thresholds::disulphide`.
+    Disulphide,
+}
+
 impl Thresholds {
+    /// Creates thresholds with the given distance for each interaction type.
+    pub fn new(
+        hydrogen: f32,
+        van_der_waals: f32,
+        ionic: f32,
+        pi_pi: f32,
+        pi_cation: f32,
+        disulphide: f32,
+    ) -> Self {
+        Thresholds { hydrogen, van_der_waals, ionic, pi_pi, pi_cation, disulphide }
+    }
+
+    /// The `strict` thresholds, each multiplied by `scale`.
+    pub fn uniform(scale: f32) -> Self {
+        let strict = Self::strict();
+
+        Thresholds {
+            hydrogen: strict.hydrogen * scale,
+            van_der_waals: strict.van_der_waals * scale,
+            ionic: strict.ionic * scale,
+            pi_pi: strict.pi_pi * scale,
+            pi_cation: strict.pi_cation * scale,
+            disulphide: strict.disulphide * scale,
+        }
+    }
+
+    /// Linear interpolation between two sets of thresholds: `t = 0` gives
+    /// `from`, and `t = 1` gives `to`. Values of `t` outside this range
+    /// extrapolate. E.g. `lerp(strict(), relaxed(), 0.5)` is halfway between.
+    pub fn lerp(from: Self, to: Self, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+
+        Thresholds {
+            hydrogen: mix(from.hydrogen, to.hydrogen),
+            van_der_waals: mix(from.van_der_waals, to.van_der_waals),
+            ionic: mix(from.ionic, to.ionic),
+            pi_pi: mix(from.pi_pi, to.pi_pi),
+            pi_cation: mix(from.pi_cation, to.pi_cation),
+            disulphide: mix(from.disulphide, to.disulphide),
+        }
+    }
+
+    /// The value of the given field.
+    pub fn get(&self, field: ThresholdField) -> f32 {
+        match field {
+            ThresholdField::Hydrogen    => self.hydrogen,
+            ThresholdField::VanDerWaals => self.van_der_waals,
+            ThresholdField::Ionic       => self.ionic,
+            ThresholdField::PiPi        => self.pi_pi,
+            ThresholdField::PiCation    => self.pi_cation,
+            ThresholdField::Disulphide  => self.disulphide,
+        }
+    }
+
+    /// Builder method for replacing the value of the given field.
+    pub fn with(mut self, field: ThresholdField, value: f32) -> Self {
+        let slot = match field {
+            ThresholdField::Hydrogen    => &mut self.hydrogen,
+            ThresholdField::VanDerWaals => &mut self.van_der_waals,
+            ThresholdField::Ionic       => &mut self.ionic,
+            ThresholdField::PiPi        => &mut self.pi_pi,
+            ThresholdField::PiCation    => &mut self.pi_cation,
+            ThresholdField::Disulphide  => &mut self.disulphide,
+        };
+
+        *slot = value;
+        self
+    }
+
+    /// Yields a copy of these thresholds for each of `values`, with `field`
+    /// replaced by that value and all other fields unchanged. Useful for
+    /// parameter sweeps over a single threshold.
+    pub fn sweep<'a>(
+        &self,
+        field: ThresholdField,
+        values: &'a [f32],
+    ) -> impl Iterator<Item=Thresholds> + 'a {
+        let base = *self;
+        values.iter().map(move |&value| base.with(field, value))
+    }
+
+    /// Whether every threshold differs from the corresponding one in `other`
+    /// by at most `epsilon`. Useful for comparing thresholds after a round
+    /// trip through their textual representation, where exact equality of
+    /// the floating-point values is not guaranteed.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        let close = |a: f32, b: f32| (a - b).abs() <= epsilon;
+
+        close(self.hydrogen, other.hydrogen)
+            && close(self.van_der_waals, other.van_der_waals)
+            && close(self.ionic, other.ionic)
+            && close(self.pi_pi, other.pi_pi)
+            && close(self.pi_cation, other.pi_cation)
+            && close(self.disulphide, other.disulphide)
+    }
+
     /// Thresholds suitable for generating a reliable network.
     pub fn strict() -> Self {
         Thresholds {
@@ -123,6 +455,40 @@ impl Thresholds {
             disulphide: 3.0,
         }
     }
+
+    /// The thresholds of the preset with the given name, i.e. `strict`
+    /// or `relaxed`, if any.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "strict"  => Some(Self::strict()),
+            "relaxed" => Some(Self::relaxed()),
+            _         => None,
+        }
+    }
+
+    /// The name of the preset these thresholds are exactly equal to, if any.
+    /// Thresholds modified in any way, even by rounding, have no name.
+    pub fn preset_name(&self) -> Option<&'static str> {
+        if *self == Self::strict() {
+            Some("strict")
+        } else if *self == Self::relaxed() {
+            Some("relaxed")
+        } else {
+            None
+        }
+    }
+}
+
+impl Settings {
+    /// Compares two sets of settings like `==`, except that thresholds are
+    /// compared using `Thresholds::approx_eq()` with the given `epsilon`.
+    /// Prefer this over `==` for settings which went through serialization,
+    /// since thresholds are transmitted as formatted strings.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        let with_same_thresholds = Settings { thresholds: other.thresholds, ..*self };
+
+        with_same_thresholds == *other && self.thresholds.approx_eq(&other.thresholds, epsilon)
+    }
 }
 
 // Default impls
@@ -139,6 +505,8 @@ impl Default for Settings {
             skip_water: true,
             skip_energy: true,
             perform_msa: false,
+            ring_md: false,
+            model: None,
         }
     }
 }
@@ -172,12 +540,20 @@ impl Default for Thresholds {
 
 impl Serialize for Settings {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let thresholds_str = serde_json::to_string(&self.thresholds)
-            .map_err(S::Error::custom)?;
-
-        let mut map = serializer.serialize_map(Some(10))?;
-
-        map.serialize_entry("ringmd", "false")?; // we always need this
+        // presets are sent by name, custom thresholds as a JSON object
+        let thresholds_str = match self.thresholds.preset_name() {
+            Some(name) => String::from(name),
+            None => serde_json::to_string(&self.thresholds).map_err(S::Error::custom)?,
+        };
+
+        // the entries below, the last two of which are optional
+        let len = 9
+            + usize::from(self.model.is_some())
+            + usize::from(self.interactions != InteractionType::Multiple);
+        let mut map = serializer.serialize_map(Some(len))?;
+
+        // !!! the server requires this, even when it's false
+        map.serialize_entry("ringmd", &self.ring_md.to_string())?;
         map.serialize_entry("chain", &self.chain)?;
         map.serialize_entry("networkPolicy", &self.network_policy)?;
         // !!! must be serialized as a string
@@ -186,9 +562,11 @@ impl Serialize for Settings {
         map.serialize_entry("nohetero", &self.skip_hetero.to_string())?;
         map.serialize_entry("nowater", &self.skip_water.to_string())?;
         map.serialize_entry("noenergy", &self.skip_energy.to_string())?;
+        // always explicit, since some servers perform MSA by default
+        map.serialize_entry("msa", &self.perform_msa.to_string())?;
 
-        if self.perform_msa {
-            map.serialize_entry("msa", "true")?;
+        if let Some(model) = self.model {
+            map.serialize_entry("model", &model.to_string())?;
         }
 
         match self.interactions {
@@ -219,6 +597,9 @@ impl<'de> Deserialize<'de> for Settings {
 
             fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
                 let mut settings = Settings::default();
+                let mut all_edges = false;
+                let mut only_first_edge = false;
+                let mut no_specific = false;
 
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
@@ -229,27 +610,27 @@ impl<'de> Deserialize<'de> for Settings {
                             settings.network_policy = map.next_value()?;
                         }
                         "allEdges" => {
-                            let _: String = map.next_value()?;
-                            settings.interactions = InteractionType::All;
+                            all_edges = parse_next_value(&mut map)?;
                         }
                         "onlyFirstEdge" => {
-                            let _: String = map.next_value()?;
-                            settings.interactions = InteractionType::MostEnergetic;
+                            only_first_edge = parse_next_value(&mut map)?;
                         }
                         "nospecific" => {
-                            let _: String = map.next_value()?;
-                            settings.interactions = InteractionType::NoSpecific;
+                            no_specific = parse_next_value(&mut map)?;
                         }
                         "seqSeparation" => {
                             settings.sequence_separation = parse_next_value(&mut map)?;
                         }
                         "thresholds" => {
                             let value_str: String = map.next_value()?;
-                            settings.thresholds = serde_json::from_str(
-                                &value_str
-                            ).map_err(
-                                M::Error::custom
-                            )?;
+                            settings.thresholds = match Thresholds::preset(&value_str) {
+                                Some(preset) => preset,
+                                None => serde_json::from_str(
+                                    &value_str
+                                ).map_err(
+                                    M::Error::custom
+                                )?,
+                            };
                         }
                         "nohetero" => {
                             settings.skip_hetero = parse_next_value(&mut map)?;
@@ -260,16 +641,39 @@ impl<'de> Deserialize<'de> for Settings {
                         "noenergy" => {
                             settings.skip_energy = parse_next_value(&mut map)?;
                         }
+                        "ringmd" => {
+                            settings.ring_md = parse_next_value(&mut map)?;
+                        }
+                        "model" => {
+                            settings.model = Some(parse_next_value(&mut map)?);
+                        }
                         "msa" => {
-                            let _: String = map.next_value()?;
-                            settings.perform_msa = true;
+                            let value: String = map.next_value()?;
+                            settings.perform_msa = match value.as_str() {
+                                "true" | "1" => true,
+                                "false" | "0" => false,
+                                _ => return Err(M::Error::invalid_value(
+                                    Unexpected::Str(&value), &"true, false, 1 or 0"
+                                )),
+                            };
                         }
                         _ => {
-                            let _: String = map.next_value()?;
+                            let _: IgnoredAny = map.next_value()?;
                         }
                     }
                 }
 
+                // The most restrictive flag wins, regardless of key order.
+                settings.interactions = if no_specific {
+                    InteractionType::NoSpecific
+                } else if only_first_edge {
+                    InteractionType::MostEnergetic
+                } else if all_edges {
+                    InteractionType::All
+                } else {
+                    InteractionType::Multiple
+                };
+
                 Ok(settings)
             }
         }
@@ -282,7 +686,7 @@ impl Serialize for Chain {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         match *self {
             Chain::All => serializer.serialize_str("all"),
-            Chain::Id(id) => serializer.serialize_char(id),
+            Chain::Id(ref id) => id.serialize(serializer),
         }
     }
 }
@@ -299,29 +703,15 @@ impl<'de> Deserialize<'de> for Chain {
             type Value = Chain;
 
             fn expecting(&self, formatter: &mut Formatter) -> FmtResult {
-                formatter.pad("a one-letter chain ID or \"all\"")
+                formatter.pad("a chain ID or \"all\"")
             }
 
             fn visit_char<E: Error>(self, v: char) -> Result<Self::Value, E> {
-                Ok(Chain::Id(v))
+                self.visit_str(v.encode_utf8(&mut [0; 4]))
             }
 
             fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
-                if v == "all" {
-                    Ok(Chain::All)
-                } else {
-                    let mut chars = v.chars();
-
-                    if let Some(c) = chars.next() {
-                        if chars.next().is_none() {
-                            Ok(Chain::Id(c))
-                        } else {
-                            Err(E::custom("multi-letter string is not a valid chain ID"))
-                        }
-                    } else {
-                       Err(E::custom("empty string is not a valid chain ID"))
-                    }
-                }
+                v.parse().map_err(E::custom)
             }
         }
 
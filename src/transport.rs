@@ -0,0 +1,111 @@
+//! Pluggable HTTP backends for `Client`.
+
+use std::fmt::Debug;
+use std::sync::{ Arc, Mutex, PoisonError };
+use std::collections::HashMap;
+use http::Response as HttpResponse;
+use reqwest::{
+    Client as ReqwestClient, Request as HttpRequest, Response, Method, StatusCode, Url,
+    header::{ HeaderValue, CONTENT_TYPE },
+};
+use crate::error::Result;
+
+/// Sends fully-built HTTP requests on behalf of a `Client`.
+///
+/// The default transport is a `reqwest::Client`. Implement this for
+/// e.g. routing requests through a custom stack, or use `MockTransport`
+/// for testing code which talks to RING without a network connection.
+pub trait Transport: Debug + Send + Sync {
+    /// Sends the request and returns the response, whatever its status.
+    /// Non-2xx responses must be returned as `Ok`, not as an error.
+    fn execute(&self, request: HttpRequest) -> Result<Response>;
+}
+
+impl Transport for ReqwestClient {
+    fn execute(&self, request: HttpRequest) -> Result<Response> {
+        ReqwestClient::execute(self, request).map_err(From::from)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn execute(&self, request: HttpRequest) -> Result<Response> {
+        (**self).execute(request)
+    }
+}
+
+/// An in-memory transport which replies with canned responses,
+/// looked up by HTTP method and endpoint.
+///
+/// An endpoint matches a request if the path of the request URL ends with
+/// it, so the base URL of the client doesn't matter. If several endpoints
+/// match, the longest one wins. Requests without a matching endpoint get
+/// a `404 Not Found` response.
+///
+/// To inspect the requests that were sent, wrap the mock in an `Arc`,
+/// pass a clone of it to `Client::with_transport()`, then call `requests()`.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    /// The status and JSON body to respond with, keyed by method and endpoint.
+    responses: HashMap<(Method, String), (StatusCode, String)>,
+    /// The method and URL of every request sent so far.
+    requests: Mutex<Vec<(Method, Url)>>,
+}
+
+impl MockTransport {
+    /// Creates a mock without any canned responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method for replying to `method` requests to `endpoint`
+    /// with the given status and JSON body.
+    pub fn respond<E, B>(mut self, method: Method, endpoint: E, status: StatusCode, body: B) -> Self
+        where E: AsRef<str>,
+              B: Into<String>,
+    {
+        let key = (method, endpoint.as_ref().trim_matches('/').to_owned());
+        self.responses.insert(key, (status, body.into()));
+        self
+    }
+
+    /// Builder method for replying to `GET` requests to `endpoint`
+    /// with `200 OK` and the given JSON body.
+    pub fn respond_ok<E: AsRef<str>, B: Into<String>>(self, endpoint: E, body: B) -> Self {
+        self.respond(Method::GET, endpoint, StatusCode::OK, body)
+    }
+
+    /// The method and URL of every request sent so far, in order.
+    pub fn requests(&self) -> Vec<(Method, Url)> {
+        self.requests.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+impl Transport for MockTransport {
+    fn execute(&self, request: HttpRequest) -> Result<Response> {
+        let path = request.url().path().trim_matches('/');
+        let canned = self.responses
+            .iter()
+            .filter(|&((method, endpoint), _)| {
+                method == request.method()
+                    && (path == endpoint || path.ends_with(&format!("/{}", endpoint)))
+            })
+            .max_by_key(|&((_, endpoint), _)| endpoint.len())
+            .map(|(_, canned)| canned.clone());
+
+        let (status, body) = canned.unwrap_or_else(|| (
+            StatusCode::NOT_FOUND,
+            format!("no canned response for {} {}", request.method(), path),
+        ));
+
+        let mut response = HttpResponse::new(body);
+        *response.status_mut() = status;
+        response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((request.method().clone(), request.url().clone()));
+
+        Ok(Response::from(response))
+    }
+}